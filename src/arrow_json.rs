@@ -0,0 +1,382 @@
+// Conversion of Arrow record batches to JSON, shared by the Parquet and
+// Arrow IPC readers so both feed the existing JSON processing pipeline.
+
+use arrow::array::timezone::Tz;
+use arrow::array::*;
+use arrow::datatypes::{ArrowNativeType, DataType, TimeUnit};
+use arrow::downcast_dictionary_array;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use chrono::{SecondsFormat, TimeZone};
+use serde_json::{Map, Value};
+
+/// Convert every row of a record batch to a JSON line (one JSON object per row).
+pub fn record_batch_to_json_lines(batch: &RecordBatch) -> Result<Vec<String>, serde_json::Error> {
+    let schema = batch.schema();
+    let mut json_lines = Vec::with_capacity(batch.num_rows());
+
+    for row_idx in 0..batch.num_rows() {
+        let mut row_map = Map::new();
+
+        for (col_idx, field) in schema.fields().iter().enumerate() {
+            let column = batch.column(col_idx);
+            let value = array_value_to_json(column, row_idx);
+            row_map.insert(field.name().clone(), value);
+        }
+
+        json_lines.push(serde_json::to_string(&Value::Object(row_map))?);
+    }
+
+    Ok(json_lines)
+}
+
+/// Convert a single value from an Arrow array at the given row index to a JSON Value.
+pub fn array_value_to_json(array: &dyn Array, idx: usize) -> Value {
+    if array.is_null(idx) {
+        return Value::Null;
+    }
+
+    match array.data_type() {
+        // P0: Integer types
+        DataType::Int8 => {
+            let arr = array.as_any().downcast_ref::<Int8Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+        DataType::Int16 => {
+            let arr = array.as_any().downcast_ref::<Int16Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+        DataType::Int32 => {
+            let arr = array.as_any().downcast_ref::<Int32Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+        DataType::Int64 => {
+            let arr = array.as_any().downcast_ref::<Int64Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+        DataType::UInt8 => {
+            let arr = array.as_any().downcast_ref::<UInt8Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+        DataType::UInt16 => {
+            let arr = array.as_any().downcast_ref::<UInt16Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+        DataType::UInt32 => {
+            let arr = array.as_any().downcast_ref::<UInt32Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+        DataType::UInt64 => {
+            let arr = array.as_any().downcast_ref::<UInt64Array>().unwrap();
+            Value::Number(arr.value(idx).into())
+        }
+
+        // P0: Float types
+        DataType::Float32 => {
+            let arr = array.as_any().downcast_ref::<Float32Array>().unwrap();
+            let val = arr.value(idx) as f64;
+            match serde_json::Number::from_f64(val) {
+                Some(n) => Value::Number(n),
+                None => Value::Null, // NaN/Inf
+            }
+        }
+        DataType::Float64 => {
+            let arr = array.as_any().downcast_ref::<Float64Array>().unwrap();
+            let val = arr.value(idx);
+            match serde_json::Number::from_f64(val) {
+                Some(n) => Value::Number(n),
+                None => Value::Null, // NaN/Inf
+            }
+        }
+
+        DataType::Float16 => {
+            let arr = array.as_any().downcast_ref::<Float16Array>().unwrap();
+            match serde_json::Number::from_f64(arr.value(idx).to_f64()) {
+                Some(n) => Value::Number(n),
+                None => Value::Null, // NaN/Inf
+            }
+        }
+
+        // Decimals are rendered as exact strings, as JSON numbers would round them
+        DataType::Decimal128(_, _) => {
+            let arr = array.as_any().downcast_ref::<Decimal128Array>().unwrap();
+            Value::String(arr.value_as_string(idx))
+        }
+        DataType::Decimal256(_, _) => {
+            let arr = array.as_any().downcast_ref::<Decimal256Array>().unwrap();
+            Value::String(arr.value_as_string(idx))
+        }
+
+        // P0: String types
+        DataType::Utf8 => {
+            let arr = array.as_any().downcast_ref::<StringArray>().unwrap();
+            Value::String(arr.value(idx).to_string())
+        }
+        // P1: LargeUtf8
+        DataType::LargeUtf8 => {
+            let arr = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
+            Value::String(arr.value(idx).to_string())
+        }
+
+        DataType::Utf8View => {
+            let arr = array.as_any().downcast_ref::<StringViewArray>().unwrap();
+            Value::String(arr.value(idx).to_string())
+        }
+
+        // Binary types -> lowercase hex
+        DataType::Binary => {
+            let arr = array.as_any().downcast_ref::<BinaryArray>().unwrap();
+            Value::String(to_hex(arr.value(idx)))
+        }
+        DataType::LargeBinary => {
+            let arr = array.as_any().downcast_ref::<LargeBinaryArray>().unwrap();
+            Value::String(to_hex(arr.value(idx)))
+        }
+        DataType::FixedSizeBinary(_) => {
+            let arr = array.as_any().downcast_ref::<FixedSizeBinaryArray>().unwrap();
+            Value::String(to_hex(arr.value(idx)))
+        }
+        DataType::BinaryView => {
+            let arr = array.as_any().downcast_ref::<BinaryViewArray>().unwrap();
+            Value::String(to_hex(arr.value(idx)))
+        }
+
+        // P0: Boolean
+        DataType::Boolean => {
+            let arr = array.as_any().downcast_ref::<BooleanArray>().unwrap();
+            Value::Bool(arr.value(idx))
+        }
+
+        // P0: Null type
+        DataType::Null => Value::Null,
+
+        // P1: Timestamp types -> ISO8601 strings
+        DataType::Timestamp(unit, tz) => {
+            let epoch_str = match unit {
+                TimeUnit::Second => {
+                    let a = array.as_any().downcast_ref::<TimestampSecondArray>().unwrap();
+                    timestamp_to_iso8601(a.value(idx), 1, tz.as_deref())
+                }
+                TimeUnit::Millisecond => {
+                    let a = array.as_any().downcast_ref::<TimestampMillisecondArray>().unwrap();
+                    timestamp_to_iso8601(a.value(idx), 1_000, tz.as_deref())
+                }
+                TimeUnit::Microsecond => {
+                    let a = array.as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
+                    timestamp_to_iso8601(a.value(idx), 1_000_000, tz.as_deref())
+                }
+                TimeUnit::Nanosecond => {
+                    let a = array.as_any().downcast_ref::<TimestampNanosecondArray>().unwrap();
+                    timestamp_to_iso8601(a.value(idx), 1_000_000_000, tz.as_deref())
+                }
+            };
+            Value::String(epoch_str)
+        }
+
+        // P1: Date types
+        DataType::Date32 => {
+            let arr = array.as_any().downcast_ref::<Date32Array>().unwrap();
+            let days = arr.value(idx);
+            let date = chrono::NaiveDate::from_num_days_from_ce_opt(days + 719_163);
+            match date {
+                Some(d) => Value::String(d.format("%Y-%m-%d").to_string()),
+                None => Value::String(days.to_string()),
+            }
+        }
+        DataType::Date64 => {
+            let arr = array.as_any().downcast_ref::<Date64Array>().unwrap();
+            let millis = arr.value(idx);
+            let secs = millis / 1000;
+            let nsecs = ((millis % 1000) * 1_000_000) as u32;
+            let dt = chrono::DateTime::from_timestamp(secs, nsecs);
+            match dt {
+                Some(d) => Value::String(d.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+                None => Value::String(millis.to_string()),
+            }
+        }
+
+        // Times of day, durations (ISO8601, e.g. PT1.5S) and intervals use
+        // Arrow's own display formatting
+        DataType::Time32(_) | DataType::Time64(_) | DataType::Duration(_) | DataType::Interval(_) => {
+            match ArrayFormatter::try_new(array, &FormatOptions::default()) {
+                Ok(formatter) => Value::String(formatter.value(idx).to_string()),
+                Err(e) => Value::String(format!("<unsupported: {}>", e)),
+            }
+        }
+
+        // Dictionary -> the referenced value
+        DataType::Dictionary(_, _) => downcast_dictionary_array!(
+            array => {
+                let key = array.keys().value(idx).as_usize();
+                array_value_to_json(array.values().as_ref(), key)
+            },
+            other => Value::String(format!("<unsupported: {}>", other))
+        ),
+
+        // P0: Struct -> nested JSON object
+        DataType::Struct(fields) => {
+            let arr = array.as_any().downcast_ref::<StructArray>().unwrap();
+            let mut obj = Map::new();
+            for (i, field) in fields.iter().enumerate() {
+                let child = arr.column(i);
+                obj.insert(field.name().clone(), array_value_to_json(child.as_ref(), idx));
+            }
+            Value::Object(obj)
+        }
+
+        // P0: List -> JSON array
+        DataType::List(_) => {
+            let arr = array.as_any().downcast_ref::<ListArray>().unwrap();
+            let values = arr.value(idx);
+            let mut items = Vec::new();
+            for i in 0..values.len() {
+                items.push(array_value_to_json(values.as_ref(), i));
+            }
+            Value::Array(items)
+        }
+
+        // P1: LargeList -> JSON array
+        DataType::LargeList(_) => {
+            let arr = array.as_any().downcast_ref::<LargeListArray>().unwrap();
+            let values = arr.value(idx);
+            let mut items = Vec::new();
+            for i in 0..values.len() {
+                items.push(array_value_to_json(values.as_ref(), i));
+            }
+            Value::Array(items)
+        }
+
+        DataType::FixedSizeList(_, _) => {
+            let arr = array.as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+            let values = arr.value(idx);
+            Value::Array((0..values.len()).map(|i| array_value_to_json(values.as_ref(), i)).collect())
+        }
+
+        // Map -> array of {"key", "value"} objects, so `-a` gives one path for
+        // all keys and one for all values however many distinct keys there are
+        DataType::Map(_, _) => {
+            let arr = array.as_any().downcast_ref::<MapArray>().unwrap();
+            let entries = arr.value(idx);
+            let keys = entries.column(0);
+            let values = entries.column(1);
+            let items = (0..entries.len())
+                .map(|i| {
+                    let mut entry = Map::new();
+                    entry.insert("key".to_string(), array_value_to_json(keys.as_ref(), i));
+                    entry.insert("value".to_string(), array_value_to_json(values.as_ref(), i));
+                    Value::Object(entry)
+                })
+                .collect();
+            Value::Array(items)
+        }
+
+        // Unsupported types - never panic
+        other => Value::String(format!("<unsupported: {}>", other)),
+    }
+}
+
+/// Convert a timestamp value to an ISO8601 string.
+/// `divisor` converts the raw value to seconds (1 for seconds, 1000 for millis, etc.)
+/// Timestamps with a timezone are shown in local time with their UTC offset
+/// (`Z` when the offset is zero). Those without one are wall-clock values and
+/// are shown without an offset. A timezone that cannot be parsed leaves the
+/// raw value, rather than guessing an offset.
+fn timestamp_to_iso8601(raw: i64, divisor: i64, tz: Option<&str>) -> String {
+    let secs = raw.div_euclid(divisor);
+    let nanos = (raw.rem_euclid(divisor) as u64 * 1_000_000_000 / divisor as u64) as u32;
+    let utc = match chrono::DateTime::from_timestamp(secs, nanos) {
+        Some(dt) => dt,
+        None => return raw.to_string(),
+    };
+    match tz.map(|tz| tz.parse::<Tz>()) {
+        Some(Ok(tz)) => tz
+            .from_utc_datetime(&utc.naive_utc())
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        Some(Err(_)) => raw.to_string(),
+        None => utc.naive_utc().format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_record_batch_to_json_lines() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec![Some("a"), None])),
+            ],
+        )
+        .unwrap();
+
+        let lines = record_batch_to_json_lines(&batch).unwrap();
+        assert_eq!(lines, vec![r#"{"id":1,"name":"a"}"#, r#"{"id":2,"name":null}"#]);
+    }
+
+    #[test]
+    fn test_extended_types() {
+        let decimal = Decimal128Array::from(vec![12345, -5]).with_precision_and_scale(10, 2).unwrap();
+        assert_eq!(array_value_to_json(&decimal, 0), "123.45");
+        assert_eq!(array_value_to_json(&decimal, 1), "-0.05");
+
+        let binary = BinaryArray::from(vec![&[0xde, 0xad, 0x01][..]]);
+        assert_eq!(array_value_to_json(&binary, 0), "dead01");
+
+        let dictionary: DictionaryArray<arrow::datatypes::Int8Type> =
+            vec!["red", "blue", "red"].into_iter().collect();
+        assert_eq!(array_value_to_json(&dictionary, 2), "red");
+
+        let time = Time32SecondArray::from(vec![3_723]);
+        assert_eq!(array_value_to_json(&time, 0), "01:02:03");
+
+        let map = {
+            let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+            builder.keys().append_value("a");
+            builder.values().append_value(1);
+            builder.append(true).unwrap();
+            builder.finish()
+        };
+        assert_eq!(array_value_to_json(&map, 0), serde_json::json!([{"key": "a", "value": 1}]));
+
+        // Above 2^53 a float would round the value
+        let id = UInt64Array::from(vec![u64::MAX]);
+        assert_eq!(array_value_to_json(&id, 0).to_string(), "18446744073709551615");
+    }
+
+    #[test]
+    fn test_timestamp_with_timezone_has_offset() {
+        let ts = TimestampSecondArray::from(vec![1_700_000_000]).with_timezone("+05:30");
+        assert_eq!(array_value_to_json(&ts, 0), "2023-11-15T03:43:20+05:30");
+        let utc = TimestampSecondArray::from(vec![1_700_000_000]).with_timezone("UTC");
+        assert_eq!(array_value_to_json(&utc, 0), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_naive_timestamp_has_no_offset() {
+        let naive = TimestampMillisecondArray::from(vec![-1]);
+        assert_eq!(array_value_to_json(&naive, 0), "1969-12-31T23:59:59.999");
+    }
+
+    #[test]
+    fn test_unparseable_timezone_keeps_raw_value() {
+        let ts = TimestampSecondArray::from(vec![1_700_000_000]).with_timezone("Mars/Olympus_Mons");
+        assert_eq!(array_value_to_json(&ts, 0), "1700000000");
+    }
+}
@@ -0,0 +1,355 @@
+// Column-wise mask profiling of Arrow data. Values are masked straight from
+// the Arrow arrays instead of going through a JSON line per row, while paths
+// and value renderings stay identical to the JSON pipeline.
+
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+use crate::arrow_json::array_value_to_json;
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+use arrow::array::*;
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+use arrow::datatypes::DataType;
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+use arrow::record_batch::RecordBatch;
+use rand::prelude::*;
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Mask frequencies and one sampled example per mask for a single path
+#[derive(Debug, Clone, Default)]
+pub struct ColumnPatterns {
+    pub name: String,
+    pub counts: HashMap<String, usize>,
+    pub examples: HashMap<String, String>,
+    /// Values that were null (rendered as the JSON literal `null`)
+    pub nulls: usize,
+}
+
+/// Profile of a set of Arrow record batches, keyed by dotted path
+#[derive(Debug, Default)]
+pub struct ArrowProfile {
+    pub rows: usize,
+    remove_array_numbers: bool,
+    /// Objects nested deeper than this are not descended into, as with `-p`
+    pathdepth: usize,
+    columns: HashMap<String, ColumnPatterns>,
+}
+
+impl ArrowProfile {
+    pub fn new(remove_array_numbers: bool, pathdepth: usize) -> Self {
+        ArrowProfile {
+            rows: 0,
+            remove_array_numbers,
+            pathdepth,
+            columns: HashMap::new(),
+        }
+    }
+
+    /// Record one rendered value (a JSON literal, so strings keep their quotes)
+    pub fn observe<F>(&mut self, path: &str, value: String, mask: &F)
+    where
+        F: Fn(&str, &str) -> String,
+    {
+        let masked = mask(&value, path);
+        if !self.columns.contains_key(path) {
+            self.columns.insert(
+                path.to_string(),
+                ColumnPatterns {
+                    name: path.to_string(),
+                    ..Default::default()
+                },
+            );
+        }
+        let column = self.columns.get_mut(path).unwrap();
+        if value == "null" {
+            column.nulls += 1;
+        }
+        let count = column.counts.entry(masked.clone()).or_insert(0);
+        *count += 1;
+
+        // Reservoir sampling
+        let mut rng = thread_rng();
+        if rng.gen::<f64>() < 1.0 / (*count as f64) {
+            column.examples.insert(masked, value);
+        }
+    }
+
+    /// Combine with a profile of other rows (e.g. another row group). Examples
+    /// are kept with probability proportional to their counts, so the result
+    /// is still a uniform sample.
+    pub fn merge(mut self, other: ArrowProfile) -> ArrowProfile {
+        self.rows += other.rows;
+        let mut rng = thread_rng();
+        for (path, other_column) in other.columns {
+            let column = match self.columns.get_mut(&path) {
+                Some(column) => column,
+                None => {
+                    self.columns.insert(path, other_column);
+                    continue;
+                }
+            };
+            column.nulls += other_column.nulls;
+            for (masked, other_count) in other_column.counts {
+                let count = column.counts.entry(masked.clone()).or_insert(0);
+                *count += other_count;
+                if rng.gen::<f64>() < other_count as f64 / *count as f64 {
+                    if let Some(example) = other_column.examples.get(&masked) {
+                        column.examples.insert(masked, example.clone());
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    /// Columns in the order the JSON pipeline would number them: keys
+    /// alphabetically at each level and array elements by position.
+    pub fn into_columns(self) -> Vec<ColumnPatterns> {
+        let mut columns: Vec<ColumnPatterns> = self.columns.into_values().collect();
+        columns.sort_by(|a, b| compare_paths(&a.name, &b.name));
+        columns
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum PathPart<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+fn path_parts(path: &str) -> Vec<PathPart<'_>> {
+    let mut parts = Vec::new();
+    for segment in path.split('.') {
+        let (key, mut rest) = match segment.find('[') {
+            Some(pos) => segment.split_at(pos),
+            None => (segment, ""),
+        };
+        parts.push(PathPart::Key(key));
+        while let Some(end) = rest.find(']') {
+            parts.push(PathPart::Index(rest[1..end].parse().unwrap_or(0)));
+            rest = &rest[end + 1..];
+        }
+    }
+    parts
+}
+
+fn compare_paths(a: &str, b: &str) -> Ordering {
+    path_parts(a).cmp(&path_parts(b))
+}
+
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+impl ArrowProfile {
+    /// Profile every column of a record batch
+    pub fn add_batch<F>(&mut self, batch: &RecordBatch, mask: &F)
+    where
+        F: Fn(&str, &str) -> String,
+    {
+        let rows: Vec<usize> = (0..batch.num_rows()).collect();
+        // Each row is an object, so its columns sit one level down
+        if self.pathdepth > 0 {
+            for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+                self.add_array(column.as_ref(), &rows, field.name(), 1, mask);
+            }
+        }
+        self.rows += batch.num_rows();
+    }
+
+    /// Profile the selected rows of an array. Structs and lists are walked
+    /// column-wise, so each child array is visited once per path. `depth`
+    /// counts the objects above the array; lists do not add a level.
+    fn add_array<F>(&mut self, array: &dyn Array, rows: &[usize], path: &str, depth: usize, mask: &F)
+    where
+        F: Fn(&str, &str) -> String,
+    {
+        match array.data_type() {
+            DataType::Struct(fields) => {
+                let arr = array.as_struct();
+                let mut present = Vec::with_capacity(rows.len());
+                for &row in rows {
+                    if arr.is_null(row) {
+                        self.observe(path, "null".to_string(), mask);
+                    } else {
+                        present.push(row);
+                    }
+                }
+                if present.is_empty() || depth >= self.pathdepth {
+                    return;
+                }
+                for (field, child) in fields.iter().zip(arr.columns()) {
+                    let child_path = format!("{}.{}", path, field.name());
+                    self.add_array(child.as_ref(), &present, &child_path, depth + 1, mask);
+                }
+            }
+            DataType::List(_) => self.add_list(array.as_list::<i32>(), rows, path, depth, mask),
+            DataType::LargeList(_) => self.add_list(array.as_list::<i64>(), rows, path, depth, mask),
+            _ => {
+                for &row in rows {
+                    match Self::leaf_literal(array, row) {
+                        Some(literal) => self.observe(path, literal, mask),
+                        None => self.add_json(&array_value_to_json(array, row), path, depth, mask),
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_list<O, F>(
+        &mut self,
+        arr: &GenericListArray<O>,
+        rows: &[usize],
+        path: &str,
+        depth: usize,
+        mask: &F,
+    )
+    where
+        O: OffsetSizeTrait,
+        F: Fn(&str, &str) -> String,
+    {
+        let offsets = arr.value_offsets();
+        // Child rows grouped by element position (or all together with -a)
+        let mut positions: Vec<Vec<usize>> = Vec::new();
+        for &row in rows {
+            if arr.is_null(row) {
+                self.observe(path, "null".to_string(), mask);
+                continue;
+            }
+            let start = offsets[row].as_usize();
+            let end = offsets[row + 1].as_usize();
+            for (position, child) in (start..end).enumerate() {
+                let slot = if self.remove_array_numbers { 0 } else { position };
+                if positions.len() <= slot {
+                    positions.resize(slot + 1, Vec::new());
+                }
+                positions[slot].push(child);
+            }
+        }
+        for (position, child_rows) in positions.iter().enumerate() {
+            let child_path = if self.remove_array_numbers {
+                format!("{}[]", path)
+            } else {
+                format!("{}[{}]", path, position)
+            };
+            self.add_array(arr.values().as_ref(), child_rows, &child_path, depth, mask);
+        }
+    }
+
+    /// Fast path for the common scalar types: the JSON literal is built
+    /// directly from the array. Other types go through `array_value_to_json`.
+    fn leaf_literal(array: &dyn Array, row: usize) -> Option<String> {
+        if array.is_null(row) {
+            return Some("null".to_string());
+        }
+        let literal = match array.data_type() {
+            DataType::Int8 => array.as_primitive::<arrow::datatypes::Int8Type>().value(row).to_string(),
+            DataType::Int16 => array.as_primitive::<arrow::datatypes::Int16Type>().value(row).to_string(),
+            DataType::Int32 => array.as_primitive::<arrow::datatypes::Int32Type>().value(row).to_string(),
+            DataType::Int64 => array.as_primitive::<arrow::datatypes::Int64Type>().value(row).to_string(),
+            DataType::UInt8 => array.as_primitive::<arrow::datatypes::UInt8Type>().value(row).to_string(),
+            DataType::UInt16 => array.as_primitive::<arrow::datatypes::UInt16Type>().value(row).to_string(),
+            DataType::UInt32 => array.as_primitive::<arrow::datatypes::UInt32Type>().value(row).to_string(),
+            DataType::UInt64 => array.as_primitive::<arrow::datatypes::UInt64Type>().value(row).to_string(),
+            DataType::Boolean => array.as_boolean().value(row).to_string(),
+            DataType::Utf8 => Value::from(array.as_string::<i32>().value(row)).to_string(),
+            DataType::LargeUtf8 => Value::from(array.as_string::<i64>().value(row)).to_string(),
+            _ => return None,
+        };
+        Some(literal)
+    }
+
+    /// Walk a converted value the way the JSON pipeline does
+    fn add_json<F>(&mut self, value: &Value, path: &str, depth: usize, mask: &F)
+    where
+        F: Fn(&str, &str) -> String,
+    {
+        match value {
+            Value::Object(map) => {
+                if depth < self.pathdepth {
+                    for (key, child) in map {
+                        self.add_json(child, &format!("{}.{}", path, key), depth + 1, mask);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (position, child) in items.iter().enumerate() {
+                    let child_path = if self.remove_array_numbers {
+                        format!("{}[]", path)
+                    } else {
+                        format!("{}[{}]", path, position)
+                    };
+                    self.add_json(child, &child_path, depth, mask);
+                }
+            }
+            other => self.observe(path, other.to_string(), mask),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Fields, Schema};
+    use std::sync::Arc;
+
+    fn identity(value: &str, _path: &str) -> String {
+        value.to_string()
+    }
+
+    #[test]
+    fn test_nested_paths_match_json_pipeline() {
+        let city = Arc::new(StringArray::from(vec![Some("Leeds"), None, Some("York")]));
+        let user_fields = Fields::from(vec![Field::new("city", DataType::Utf8, true)]);
+        let user = StructArray::new(
+            user_fields.clone(),
+            vec![city as ArrayRef],
+            Some(vec![true, true, false].into()),
+        );
+        let tags = ListArray::from_iter_primitive::<arrow::datatypes::Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![Some(3)]),
+        ]);
+        let schema = Schema::new(vec![
+            Field::new("user", DataType::Struct(user_fields), true),
+            Field::new("tags", tags.data_type().clone(), true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(user), Arc::new(tags)],
+        )
+        .unwrap();
+
+        let mut profile = ArrowProfile::new(false, 9);
+        profile.add_batch(&batch, &identity);
+        assert_eq!(profile.rows, 3);
+
+        let columns = profile.into_columns();
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["tags", "tags[0]", "tags[1]", "user", "user.city"]);
+        let city = &columns[4];
+        assert_eq!(city.counts.get("\"Leeds\""), Some(&1));
+        assert_eq!(city.counts.get("null"), Some(&1));
+        assert_eq!(city.nulls, 1);
+        assert_eq!(columns[3].counts.get("null"), Some(&1));
+        assert_eq!(columns[1].counts.len(), 2);
+
+        let mut collapsed = ArrowProfile::new(true, 9);
+        collapsed.add_batch(&batch, &identity);
+        let merged = collapsed.merge(ArrowProfile::new(true, 9));
+        let columns = merged.into_columns();
+        assert_eq!(columns[1].name, "tags[]");
+        assert_eq!(columns[1].counts.values().sum::<usize>(), 3);
+
+        // As with -p 1 on JSON, only null structs are reported below the cut-off
+        let mut shallow = ArrowProfile::new(false, 1);
+        shallow.add_batch(&batch, &identity);
+        let columns = shallow.into_columns();
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["tags", "tags[0]", "tags[1]", "user"]);
+    }
+}
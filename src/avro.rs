@@ -0,0 +1,177 @@
+#[cfg(feature = "avro")]
+use apache_avro::types::Value as AvroValue;
+#[cfg(feature = "avro")]
+use apache_avro::Reader;
+#[cfg(feature = "avro")]
+use serde_json::{Map, Value};
+#[cfg(feature = "avro")]
+use std::fs::File;
+#[cfg(feature = "avro")]
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+
+#[cfg(feature = "avro")]
+/// Reader for Avro object container files. The writer schema embedded in
+/// the file header is used to decode every record.
+pub struct AvroReader;
+
+#[cfg(feature = "avro")]
+impl AvroReader {
+    /// Read an Avro object container file and return JSON lines (one JSON object per record).
+    /// This feeds into the existing JSON processing pipeline.
+    pub fn read_as_json_lines<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let file = File::open(&path)?;
+        Self::read_from_reader(BufReader::new(file))
+    }
+
+    /// Read an Avro object container file from any reader, such as stdin.
+    pub fn read_from_reader<R: Read>(
+        input: R,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let reader = Reader::new(input)?;
+        let mut json_lines = Vec::new();
+
+        for record in reader {
+            let value = Self::avro_value_to_json(record?);
+            json_lines.push(serde_json::to_string(&value)?);
+        }
+
+        Ok(json_lines)
+    }
+
+    /// Convert an Avro value to JSON, rendering logical types (dates,
+    /// timestamps, UUIDs) as ISO8601 / canonical strings like the Parquet reader.
+    fn avro_value_to_json(value: AvroValue) -> Value {
+        match value {
+            AvroValue::Null => Value::Null,
+            AvroValue::Boolean(b) => Value::Bool(b),
+            AvroValue::Int(i) => Value::Number(i.into()),
+            AvroValue::Long(l) => Value::Number(l.into()),
+            AvroValue::Float(f) => serde_json::Number::from_f64(f as f64)
+                .map(Value::Number)
+                .unwrap_or(Value::Null), // NaN/Inf
+            AvroValue::Double(d) => serde_json::Number::from_f64(d)
+                .map(Value::Number)
+                .unwrap_or(Value::Null), // NaN/Inf
+            AvroValue::String(s) => Value::String(s),
+            AvroValue::Bytes(bytes) | AvroValue::Fixed(_, bytes) => Value::String(
+                bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+            ),
+            AvroValue::Enum(_, symbol) => Value::String(symbol),
+            AvroValue::Union(_, inner) => Self::avro_value_to_json(*inner),
+            AvroValue::Array(items) => {
+                Value::Array(items.into_iter().map(Self::avro_value_to_json).collect())
+            }
+            AvroValue::Map(entries) => {
+                let mut obj = Map::new();
+                for (key, value) in entries {
+                    obj.insert(key, Self::avro_value_to_json(value));
+                }
+                Value::Object(obj)
+            }
+            AvroValue::Record(fields) => {
+                let mut obj = Map::new();
+                for (name, value) in fields {
+                    obj.insert(name, Self::avro_value_to_json(value));
+                }
+                Value::Object(obj)
+            }
+            AvroValue::Date(days) => {
+                match chrono::NaiveDate::from_num_days_from_ce_opt(days + 719_163) {
+                    Some(d) => Value::String(d.format("%Y-%m-%d").to_string()),
+                    None => Value::String(days.to_string()),
+                }
+            }
+            AvroValue::TimeMillis(ms) => Value::String(Self::time_of_day(ms as i64 * 1_000_000)),
+            AvroValue::TimeMicros(us) => Value::String(Self::time_of_day(us * 1_000)),
+            AvroValue::TimestampMillis(ms) => Value::String(Self::timestamp(ms, 1_000)),
+            AvroValue::TimestampMicros(us) => Value::String(Self::timestamp(us, 1_000_000)),
+            AvroValue::Uuid(uuid) => Value::String(uuid.to_string()),
+            // Remaining logical types (decimal, duration, local timestamps) use
+            // the library's own JSON rendering
+            other => Value::try_from(other)
+                .unwrap_or_else(|e| Value::String(format!("<unsupported: {}>", e))),
+        }
+    }
+
+    fn timestamp(raw: i64, per_second: i64) -> String {
+        let secs = raw.div_euclid(per_second);
+        let nanos = (raw.rem_euclid(per_second) * (1_000_000_000 / per_second)) as u32;
+        match chrono::DateTime::from_timestamp(secs, nanos) {
+            Some(dt) => dt.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string(),
+            None => raw.to_string(),
+        }
+    }
+
+    fn time_of_day(nanos_since_midnight: i64) -> String {
+        let secs = (nanos_since_midnight / 1_000_000_000) as u32;
+        let nanos = (nanos_since_midnight % 1_000_000_000) as u32;
+        match chrono::NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos) {
+            Some(t) => t.format("%H:%M:%S%.f").to_string(),
+            None => nanos_since_midnight.to_string(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dummy implementation when avro feature is not enabled
+// ---------------------------------------------------------------------------
+
+#[cfg(not(feature = "avro"))]
+pub struct AvroReader;
+
+#[cfg(not(feature = "avro"))]
+impl AvroReader {
+    pub fn read_as_json_lines<P: AsRef<Path>>(
+        _path: P,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Err("Avro support not enabled. Rebuild with --features avro".into())
+    }
+
+    pub fn read_from_reader<R: Read>(
+        _input: R,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Err("Avro support not enabled. Rebuild with --features avro".into())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+#[cfg(feature = "avro")]
+mod tests {
+    use super::*;
+    use apache_avro::{Schema, Writer};
+
+    #[test]
+    fn test_read_container_file() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "customer",
+                "fields": [
+                    {"name": "id", "type": "long"},
+                    {"name": "name", "type": ["null", "string"]},
+                    {"name": "joined", "type": {"type": "int", "logicalType": "date"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mut writer = Writer::new(&schema, Vec::new());
+        let mut record = apache_avro::types::Record::new(&schema).unwrap();
+        record.put("id", 42i64);
+        record.put("name", AvroValue::Union(1, Box::new(AvroValue::String("Ann".into()))));
+        record.put("joined", AvroValue::Date(16_800));
+        writer.append(record).unwrap();
+        let bytes = writer.into_inner().unwrap();
+
+        let lines = AvroReader::read_from_reader(&bytes[..]).unwrap();
+        assert_eq!(lines, vec![r#"{"id":42,"joined":"2015-12-31","name":"Ann"}"#]);
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::{BTreeMap, HashMap};
+use unic::normal::StrNormalForm;
+use unic::ucd::GeneralCategory;
+
+/// Maximum number of example row numbers retained per (column, character)
+const MAX_EXAMPLE_ROWS: usize = 5;
+
+/// The set of characters considered "expected" in a column.
+/// Anything outside this set is reported by the per-column character profile.
+#[derive(Debug, Clone)]
+pub struct ExpectedChars {
+    name: String,
+    ranges: Vec<(char, char)>,
+}
+
+impl ExpectedChars {
+    /// Parse an expected character set specification.
+    ///
+    /// Accepts one of the presets `ascii` (printable ASCII), `latin1`
+    /// (printable ASCII plus printable Latin-1, excluding NBSP) or `alnum`
+    /// (ASCII letters, digits and space), or a custom set such as
+    /// `a-zA-Z0-9 .,'\-`. Custom sets support ranges (`a-z`), the escapes
+    /// `\t`, `\\`, `\-` and `\u{00E9}`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let ranges = match spec {
+            "ascii" => vec![(' ', '~')],
+            "latin1" => vec![(' ', '~'), ('\u{00A1}', '\u{00FF}')],
+            "alnum" => vec![('a', 'z'), ('A', 'Z'), ('0', '9'), (' ', ' ')],
+            custom => Self::parse_custom(custom)?,
+        };
+
+        Ok(ExpectedChars {
+            name: spec.to_string(),
+            ranges,
+        })
+    }
+
+    fn parse_custom(spec: &str) -> Result<Vec<(char, char)>, String> {
+        if spec.is_empty() {
+            return Err("Expected character set must not be empty".to_string());
+        }
+
+        // First resolve escapes into a flat list of (char, is_literal) tokens,
+        // where literal tokens can never act as a range operator.
+        let mut tokens: Vec<(char, bool)> = Vec::new();
+        let mut chars = spec.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                tokens.push((c, false));
+                continue;
+            }
+            match chars.next() {
+                Some('t') => tokens.push(('\t', true)),
+                Some('\\') => tokens.push(('\\', true)),
+                Some('-') => tokens.push(('-', true)),
+                Some('u') => {
+                    if chars.next() != Some('{') {
+                        return Err(format!("Invalid unicode escape in '{}'", spec));
+                    }
+                    let hex: String = chars.by_ref().take_while(|&h| h != '}').collect();
+                    let code = u32::from_str_radix(&hex, 16)
+                        .map_err(|_| format!("Invalid unicode escape '\\u{{{}}}'", hex))?;
+                    let decoded = char::from_u32(code)
+                        .ok_or_else(|| format!("Invalid code point U+{:X}", code))?;
+                    tokens.push((decoded, true));
+                }
+                Some(other) => return Err(format!("Unknown escape '\\{}'", other)),
+                None => return Err("Trailing backslash in expected character set".to_string()),
+            }
+        }
+
+        let mut ranges = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let (start, _) = tokens[i];
+            let is_range = i + 2 < tokens.len() && tokens[i + 1] == ('-', false);
+            if is_range {
+                let (end, _) = tokens[i + 2];
+                if end < start {
+                    return Err(format!("Invalid range '{}-{}'", start, end));
+                }
+                ranges.push((start, end));
+                i += 3;
+            } else {
+                ranges.push((start, start));
+                i += 1;
+            }
+        }
+
+        Ok(ranges)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(start, end)| c >= start && c <= end)
+    }
+}
+
+/// Column index, name and its unexpected characters, as listed by
+/// `ColumnCharProfile::columns`
+pub type ColumnChars<'a> = (usize, &'a str, Vec<(char, &'a CharStats)>);
+
+/// Occurrences of one unexpected character within one column.
+#[derive(Debug, Clone, Default)]
+pub struct CharStats {
+    pub count: usize,
+    /// Row numbers (1-based, data rows only) where the character was first seen
+    pub example_rows: Vec<usize>,
+    /// The first value in which the character was seen
+    pub example_value: String,
+}
+
+/// Character frequency profile split by column, recording only characters
+/// outside the expected set.
+pub struct ColumnCharProfile {
+    expected: ExpectedChars,
+    column_index: HashMap<String, usize>,
+    columns: Vec<(String, HashMap<char, CharStats>)>,
+}
+
+impl ColumnCharProfile {
+    pub fn new(expected: ExpectedChars) -> Self {
+        ColumnCharProfile {
+            expected,
+            column_index: HashMap::new(),
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn expected(&self) -> &ExpectedChars {
+        &self.expected
+    }
+
+    /// Register a column so it is reported even if it contains no unexpected characters.
+    pub fn add_column(&mut self, column: &str) -> usize {
+        if let Some(&idx) = self.column_index.get(column) {
+            return idx;
+        }
+        let idx = self.columns.len();
+        self.column_index.insert(column.to_string(), idx);
+        self.columns.push((column.to_string(), HashMap::new()));
+        idx
+    }
+
+    /// Record every unexpected character in a single field value.
+    pub fn add_value(&mut self, column: &str, value: &str, row: usize) {
+        let idx = self.add_column(column);
+        let expected = &self.expected;
+        let stats_map = &mut self.columns[idx].1;
+
+        for c in value.chars().filter(|&c| !expected.contains(c)) {
+            let stats = stats_map.entry(c).or_default();
+            stats.count += 1;
+            if stats.example_value.is_empty() {
+                stats.example_value = value.to_string();
+            }
+            if stats.example_rows.last() != Some(&row) && stats.example_rows.len() < MAX_EXAMPLE_ROWS {
+                stats.example_rows.push(row);
+            }
+        }
+    }
+
+    /// Columns in first-seen order, each with its unexpected characters sorted by code point.
+    pub fn columns(&self) -> Vec<ColumnChars<'_>> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, stats_map))| {
+                let mut chars: Vec<(char, &CharStats)> =
+                    stats_map.iter().map(|(c, stats)| (*c, stats)).collect();
+                chars.sort_unstable_by_key(|(c, _)| *c as u32);
+                (idx, name.as_str(), chars)
+            })
+            .collect()
+    }
+}
+
+/// Categories of characters that commonly cause silent data quality problems:
+/// they look like something else, are invisible, or change under normalization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SuspiciousCategory {
+    ZeroWidth,
+    BidiControl,
+    Confusable,
+    CombiningMark,
+    NfcUnstable,
+    NfkcUnstable,
+}
+
+impl SuspiciousCategory {
+    /// Short tag used in the flags column of the character report
+    pub fn label(&self) -> &'static str {
+        match self {
+            SuspiciousCategory::ZeroWidth => "ZERO_WIDTH",
+            SuspiciousCategory::BidiControl => "BIDI",
+            SuspiciousCategory::Confusable => "CONFUSABLE",
+            SuspiciousCategory::CombiningMark => "COMBINING",
+            SuspiciousCategory::NfcUnstable => "NFC",
+            SuspiciousCategory::NfkcUnstable => "NFKC",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SuspiciousCategory::ZeroWidth => "Zero-width / invisible characters",
+            SuspiciousCategory::BidiControl => "Bidirectional text controls",
+            SuspiciousCategory::Confusable => "Confusables / homoglyphs of Latin characters",
+            SuspiciousCategory::CombiningMark => "Combining marks",
+            SuspiciousCategory::NfcUnstable => "Changed by NFC normalization",
+            SuspiciousCategory::NfkcUnstable => "Changed by NFKC normalization",
+        }
+    }
+}
+
+/// Returns the ASCII character that `c` is commonly mistaken for, if any.
+/// Covers Cyrillic and Greek homoglyphs of Latin letters plus typographic
+/// dashes and quotes that are often pasted in place of their ASCII forms.
+pub fn confusable_with(c: char) -> Option<char> {
+    let target = match c {
+        // Cyrillic lowercase
+        '\u{0430}' => 'a', '\u{0435}' => 'e', '\u{043E}' => 'o', '\u{0440}' => 'p',
+        '\u{0441}' => 'c', '\u{0443}' => 'y', '\u{0445}' => 'x', '\u{0456}' => 'i',
+        '\u{0458}' => 'j', '\u{0455}' => 's', '\u{0501}' => 'd', '\u{04BB}' => 'h',
+        '\u{051B}' => 'q', '\u{051D}' => 'w',
+        // Cyrillic uppercase
+        '\u{0410}' => 'A', '\u{0412}' => 'B', '\u{0415}' => 'E', '\u{041A}' => 'K',
+        '\u{041C}' => 'M', '\u{041D}' => 'H', '\u{041E}' => 'O', '\u{0420}' => 'P',
+        '\u{0421}' => 'C', '\u{0422}' => 'T', '\u{0425}' => 'X', '\u{0406}' => 'I',
+        '\u{0408}' => 'J', '\u{0405}' => 'S',
+        // Greek
+        '\u{03BF}' => 'o', '\u{03BD}' => 'v', '\u{0391}' => 'A', '\u{0392}' => 'B',
+        '\u{0395}' => 'E', '\u{0396}' => 'Z', '\u{0397}' => 'H', '\u{0399}' => 'I',
+        '\u{039A}' => 'K', '\u{039C}' => 'M', '\u{039D}' => 'N', '\u{039F}' => 'O',
+        '\u{03A1}' => 'P', '\u{03A4}' => 'T', '\u{03A5}' => 'Y', '\u{03A7}' => 'X',
+        // Latin lookalikes
+        '\u{0131}' => 'i', '\u{0261}' => 'g',
+        // Dashes, minus signs and quotes
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2212}' => '-',
+        '\u{2018}' | '\u{2019}' | '\u{201B}' | '\u{2032}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201F}' | '\u{2033}' => '"',
+        _ => return None,
+    };
+    Some(target)
+}
+
+pub fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{180E}' | '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+    )
+}
+
+pub fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Non-spacing and enclosing marks, which sit on the previous character.
+/// Spacing marks (Mc), such as most Indic vowel signs, are ordinary letters
+/// of their scripts and are not flagged.
+pub fn is_combining_mark(c: char) -> bool {
+    matches!(
+        GeneralCategory::of(c),
+        GeneralCategory::NonspacingMark | GeneralCategory::EnclosingMark
+    )
+}
+
+/// All suspicious categories that apply to a single character.
+pub fn suspicious_categories(c: char) -> Vec<SuspiciousCategory> {
+    let mut categories = Vec::new();
+    if is_zero_width(c) {
+        categories.push(SuspiciousCategory::ZeroWidth);
+    }
+    if is_bidi_control(c) {
+        categories.push(SuspiciousCategory::BidiControl);
+    }
+    if confusable_with(c).is_some() {
+        categories.push(SuspiciousCategory::Confusable);
+    }
+    if is_combining_mark(c) {
+        categories.push(SuspiciousCategory::CombiningMark);
+    }
+
+    let mut buf = [0u8; 4];
+    let original: &str = c.encode_utf8(&mut buf);
+    if !original.nfc().eq(original.chars()) {
+        categories.push(SuspiciousCategory::NfcUnstable);
+    }
+    if !original.nfkc().eq(original.chars()) {
+        categories.push(SuspiciousCategory::NfkcUnstable);
+    }
+    categories
+}
+
+/// Flags column text for a character, e.g. `CONFUSABLE(a) NFKC`
+pub fn suspicious_flags(c: char) -> String {
+    suspicious_categories(c)
+        .iter()
+        .map(|category| match (category, confusable_with(c)) {
+            (SuspiciousCategory::Confusable, Some(target)) => format!("CONFUSABLE({})", target),
+            _ => category.label().to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Summary of suspicious characters seen in a character profile, plus the
+/// base + combining mark sequences found while scanning lines.
+#[derive(Debug, Default)]
+pub struct SuspiciousSummary {
+    /// category -> (distinct characters, total occurrences)
+    pub categories: BTreeMap<SuspiciousCategory, (usize, usize)>,
+    pub combining_sequences: usize,
+    /// Combining sequences that NFC would compose into a precomposed character
+    pub composable_sequences: usize,
+}
+
+impl SuspiciousSummary {
+    pub fn add_char(&mut self, c: char, count: usize) {
+        for category in suspicious_categories(c) {
+            let entry = self.categories.entry(category).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += count;
+        }
+    }
+
+    /// Count base character + combining mark sequences within a line.
+    pub fn add_line(&mut self, line: &str) {
+        let mut sequence = String::new();
+        let mut marks = 0;
+
+        for c in line.chars() {
+            if is_combining_mark(c) && !sequence.is_empty() {
+                sequence.push(c);
+                marks += 1;
+                continue;
+            }
+            self.finish_sequence(&sequence, marks);
+            sequence.clear();
+            marks = 0;
+            sequence.push(c);
+        }
+        self.finish_sequence(&sequence, marks);
+    }
+
+    fn finish_sequence(&mut self, sequence: &str, marks: usize) {
+        if marks == 0 {
+            return;
+        }
+        self.combining_sequences += 1;
+        if !sequence.nfc().eq(sequence.chars()) {
+            self.composable_sequences += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_chars_presets_and_custom() {
+        let ascii = ExpectedChars::parse("ascii").unwrap();
+        assert!(ascii.contains('a'));
+        assert!(!ascii.contains('\u{00A0}'));
+        assert!(!ascii.contains('\t'));
+
+        let custom = ExpectedChars::parse("a-c\\-\\t\\u{00E9}").unwrap();
+        assert!(custom.contains('b'));
+        assert!(custom.contains('-'));
+        assert!(custom.contains('\t'));
+        assert!(custom.contains('é'));
+        assert!(!custom.contains('d'));
+
+        assert!(ExpectedChars::parse("z-a").is_err());
+    }
+
+    #[test]
+    fn test_column_profile_records_rows() {
+        let mut profile = ColumnCharProfile::new(ExpectedChars::parse("ascii").unwrap());
+        profile.add_value("name", "Smith\u{00A0}", 1);
+        profile.add_value("name", "Jones", 2);
+        profile.add_value("name", "\u{00A0}Brown\u{00A0}", 3);
+        profile.add_value("city", "Leeds", 1);
+
+        let columns = profile.columns();
+        assert_eq!(columns.len(), 2);
+        let (_, name, chars) = &columns[0];
+        assert_eq!(*name, "name");
+        assert_eq!(chars.len(), 1);
+        assert_eq!(chars[0].1.count, 3);
+        assert_eq!(chars[0].1.example_rows, vec![1, 3]);
+        assert!(columns[1].2.is_empty());
+    }
+
+    #[test]
+    fn test_suspicious_categories() {
+        assert_eq!(confusable_with('\u{0430}'), Some('a'));
+        assert_eq!(suspicious_flags('\u{0430}'), "CONFUSABLE(a)");
+        assert!(suspicious_categories('a').is_empty());
+        assert_eq!(suspicious_categories('\u{200B}'), vec![SuspiciousCategory::ZeroWidth]);
+        assert_eq!(suspicious_categories('\u{202E}'), vec![SuspiciousCategory::BidiControl]);
+        // Angstrom sign normalizes to A WITH RING ABOVE under both forms
+        assert!(suspicious_categories('\u{212B}').contains(&SuspiciousCategory::NfcUnstable));
+        // NBSP only changes under compatibility normalization
+        assert_eq!(suspicious_categories('\u{00A0}'), vec![SuspiciousCategory::NfkcUnstable]);
+        assert_eq!(suspicious_categories('\u{0301}'), vec![SuspiciousCategory::CombiningMark]);
+        // DEVANAGARI VOWEL SIGN AA is a spacing mark
+        assert!(suspicious_categories('\u{093E}').is_empty());
+    }
+
+    #[test]
+    fn test_combining_sequences() {
+        let mut summary = SuspiciousSummary::default();
+        summary.add_line("Cafe\u{0301} and cafe\u{0301}\u{0302} and q\u{0307}");
+        assert_eq!(summary.combining_sequences, 3);
+        // e + acute composes to é; q + dot above has no precomposed form
+        assert_eq!(summary.composable_sequences, 2);
+    }
+}
@@ -0,0 +1,342 @@
+// Standardised values from the assertion rules written back into the records,
+// so a profiling run can also produce a cleaned copy of its input.
+
+use crate::config::load_config;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CleanseAction {
+    /// Overwrite the original value
+    Replace,
+    /// Add the value as a new column, or a sibling key in JSON
+    #[default]
+    Append,
+}
+
+/// Use the rule output `key` for `column`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Mapping {
+    /// Column name as in the DQ report; JSON array indices are written `[]`
+    pub column: String,
+    pub key: String,
+    #[serde(default)]
+    pub action: CleanseAction,
+    /// Name of the appended column; defaults to `<column>_<key>`
+    #[serde(rename = "as")]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MappingFile {
+    mappings: Vec<Mapping>,
+}
+
+/// The changes to make for one value
+#[derive(Debug, Default, PartialEq)]
+pub struct FieldChanges {
+    pub replace: Option<Value>,
+    /// New column names and values
+    pub append: Vec<(String, Value)>,
+}
+
+/// Decides which rule outputs end up in the cleansed records
+#[derive(Debug, Clone, Default)]
+pub struct Cleanser {
+    /// None appends every `std_*` output
+    mappings: Option<Vec<Mapping>>,
+}
+
+impl Cleanser {
+    /// Append every standardised (`std_*`) value a rule produces
+    pub fn append_all() -> Self {
+        Cleanser { mappings: None }
+    }
+
+    pub fn with_mappings(mappings: Vec<Mapping>) -> Self {
+        Cleanser { mappings: Some(mappings) }
+    }
+
+    /// Load mappings from a `.toml` file, or YAML for any other extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file: MappingFile = load_config(path)?;
+        Ok(Cleanser::with_mappings(file.mappings))
+    }
+
+    /// Changes for a value of `column`, given the rule results for it
+    pub fn changes(&self, column: &str, results: &Map<String, Value>) -> FieldChanges {
+        let mut changes = FieldChanges::default();
+        let append_name = |key: &str| format!("{}_{}", leaf_name(column), key);
+        match &self.mappings {
+            None => {
+                for (key, value) in results.iter().filter(|(key, _)| key.starts_with("std_")) {
+                    changes.append.push((append_name(key), value.clone()));
+                }
+            }
+            Some(mappings) => {
+                for mapping in mappings.iter().filter(|m| m.column == column) {
+                    let value = match results.get(&mapping.key) {
+                        Some(value) => value.clone(),
+                        None => continue,
+                    };
+                    match mapping.action {
+                        CleanseAction::Replace => changes.replace = Some(value),
+                        CleanseAction::Append => {
+                            let name = mapping.output.clone().unwrap_or_else(|| append_name(&mapping.key));
+                            changes.append.push((name, value));
+                        }
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    /// Cleanse one JSON record given as text. `evaluate` runs the rules for a
+    /// field path and raw value. Only replaced values and appended keys are
+    /// written; everything else, including key order and number formatting,
+    /// is copied from the input. Values inside arrays can be replaced but have
+    /// nowhere to append to.
+    pub fn cleanse_json<F>(&self, record: &str, evaluate: &F) -> Result<String, String>
+    where
+        F: Fn(&str, &str) -> Map<String, Value>,
+    {
+        let mut patcher = JsonPatcher {
+            cleanser: self,
+            evaluate,
+            text: record,
+            pos: 0,
+            out: String::with_capacity(record.len()),
+        };
+        patcher.value("")?;
+        patcher.out.push_str(&record[patcher.pos..]);
+        Ok(patcher.out)
+    }
+}
+
+/// Copies a JSON record through, rewriting the values the cleanser changes
+struct JsonPatcher<'a, F> {
+    cleanser: &'a Cleanser,
+    evaluate: &'a F,
+    text: &'a str,
+    pos: usize,
+    out: String,
+}
+
+impl<F> JsonPatcher<'_, F>
+where
+    F: Fn(&str, &str) -> Map<String, Value>,
+{
+    /// Copy one value at `path`, returning the changes of a scalar, or None
+    /// for objects, arrays and nulls
+    fn value(&mut self, path: &str) -> Result<Option<FieldChanges>, String> {
+        self.whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(path).map(|_| None),
+            Some(b'[') => self.array(path).map(|_| None),
+            _ => self.scalar(path),
+        }
+    }
+
+    fn object(&mut self, path: &str) -> Result<(), String> {
+        self.expect(b'{')?;
+        let mut keys = HashSet::new();
+        let mut appended = Vec::new();
+        self.whitespace();
+        while self.peek() != Some(b'}') {
+            let start = self.pos;
+            self.skip_string()?;
+            let token = &self.text[start..self.pos];
+            self.out.push_str(token);
+            let key: String = serde_json::from_str(token).map_err(|e| e.to_string())?;
+            self.whitespace();
+            self.expect(b':')?;
+            let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+            if let Some(changes) = self.value(&child_path)? {
+                appended.extend(changes.append);
+            }
+            keys.insert(key);
+            self.whitespace();
+            if self.peek() == Some(b',') {
+                self.expect(b',')?;
+                self.whitespace();
+            }
+        }
+        // Appended names are relative to this object, and never overwrite a key
+        for (name, value) in appended {
+            let name = unique_name(&name, &keys);
+            self.out.push(',');
+            self.out.push_str(&Value::String(name.clone()).to_string());
+            self.out.push(':');
+            self.out.push_str(&value.to_string());
+            keys.insert(name);
+        }
+        self.expect(b'}')
+    }
+
+    fn array(&mut self, path: &str) -> Result<(), String> {
+        self.expect(b'[')?;
+        let child_path = format!("{}[]", path);
+        self.whitespace();
+        while self.peek() != Some(b']') {
+            self.value(&child_path)?;
+            self.whitespace();
+            if self.peek() == Some(b',') {
+                self.expect(b',')?;
+                self.whitespace();
+            }
+        }
+        self.expect(b']')
+    }
+
+    fn scalar(&mut self, path: &str) -> Result<Option<FieldChanges>, String> {
+        let start = self.pos;
+        if self.peek() == Some(b'"') {
+            self.skip_string()?;
+        } else {
+            let len = self.text[start..]
+                .find(|c: char| matches!(c, ',' | '}' | ']') || c.is_whitespace())
+                .unwrap_or(self.text.len() - start);
+            self.pos += len;
+        }
+        let token = &self.text[start..self.pos];
+        // Numbers and booleans reach the rules as written, not re-rendered
+        let raw = match serde_json::from_str::<Value>(token).map_err(|e| e.to_string())? {
+            Value::String(s) => s,
+            Value::Null => {
+                self.out.push_str(token);
+                return Ok(None);
+            }
+            _ => token.to_string(),
+        };
+        let mut changes = self.cleanser.changes(path, &(self.evaluate)(path, &raw));
+        match changes.replace.take() {
+            Some(replacement) => self.out.push_str(&replacement.to_string()),
+            None => self.out.push_str(token),
+        }
+        Ok(Some(changes))
+    }
+
+    /// Step over a string literal without copying it
+    fn skip_string(&mut self) -> Result<(), String> {
+        let bytes = self.text.as_bytes();
+        if self.peek() != Some(b'"') {
+            return Err(format!("Expected a string at byte {}", self.pos));
+        }
+        self.pos += 1;
+        while self.pos < bytes.len() {
+            match bytes[self.pos] {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => self.pos += 1,
+            }
+        }
+        Err("Unterminated string".to_string())
+    }
+
+    fn whitespace(&mut self) {
+        let len = self.text[self.pos..].len() - self.text[self.pos..].trim_start().len();
+        self.out.push_str(&self.text[self.pos..self.pos + len]);
+        self.pos += len;
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        if self.peek() != Some(byte) {
+            return Err(format!("Expected '{}' at byte {}", byte as char, self.pos));
+        }
+        self.out.push(byte as char);
+        self.pos += 1;
+        Ok(())
+    }
+}
+
+/// `name`, or `name_2`, `name_3`, ... if that is already taken
+pub fn unique_name(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap()
+}
+
+/// Last segment of a dotted JSON path, used to name appended keys
+fn leaf_name(column: &str) -> &str {
+    column.rsplit('.').next().unwrap_or(column)
+}
+
+/// A rule output as text for delimited output
+pub fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn evaluate(path: &str, raw: &str) -> Map<String, Value> {
+        let results = match (path, raw) {
+            (_, "31/12/2015") => json!({ "std_date": "2015-12-31", "string_length": 10 }),
+            ("address.country", "UK") => json!({ "std_country_iso3": "GBR" }),
+            _ => json!({}),
+        };
+        results.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_append_all_adds_std_values_next_to_their_value() {
+        let record = r#"{"joined": "31/12/2015", "address": {"country": "UK"}}"#;
+        assert_eq!(
+            Cleanser::append_all().cleanse_json(record, &evaluate).unwrap(),
+            r#"{"joined": "31/12/2015", "address": {"country": "UK","country_std_country_iso3":"GBR"},"joined_std_date":"2015-12-31"}"#
+        );
+    }
+
+    #[test]
+    fn test_mappings() {
+        let file: MappingFile = serde_yaml::from_str(
+            "mappings:\n  - { column: joined, key: std_date, action: replace }\n  - { column: 'dates[]', key: std_date, action: replace }\n  - { column: joined, key: string_length, as: joined_len }\n",
+        )
+        .unwrap();
+        let cleanser = Cleanser::with_mappings(file.mappings);
+
+        // Untouched values keep their order and formatting, even past f64 precision
+        let record = r#"{"z": 1.50, "joined": "31/12/2015", "id": 12345678901234567890, "dates": ["31/12/2015", "x"]}"#;
+        assert_eq!(
+            cleanser.cleanse_json(record, &evaluate).unwrap(),
+            r#"{"z": 1.50, "joined": "2015-12-31", "id": 12345678901234567890, "dates": ["2015-12-31", "x"],"joined_len":10}"#
+        );
+
+        // An appended name that is already a key gets a suffix
+        let record = r#"{"joined":"31/12/2015","joined_len":"kept"}"#;
+        assert_eq!(
+            cleanser.cleanse_json(record, &evaluate).unwrap(),
+            r#"{"joined":"2015-12-31","joined_len":"kept","joined_len_2":10}"#
+        );
+
+        let changes = cleanser.changes("joined", &evaluate("joined", "31/12/2015"));
+        assert_eq!(changes.replace, Some(json!("2015-12-31")));
+        assert_eq!(changes.append, vec![("joined_len".to_string(), json!(10))]);
+        assert_eq!(value_text(&changes.append[0].1), "10");
+
+        let mapping: Mapping = serde_yaml::from_str("{ column: joined, key: std_date }").unwrap();
+        assert_eq!(mapping.action, CleanseAction::Append);
+    }
+}
@@ -0,0 +1,19 @@
+// Loading of the YAML and TOML files that configure rules, treatments,
+// expectations and cleansing.
+
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// Load a `.toml` file as TOML, or any other extension as YAML
+pub fn load_config<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(&path)?;
+    let is_toml = path
+        .as_ref()
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    if is_toml {
+        Ok(toml::from_str(&text)?)
+    } else {
+        Ok(serde_yaml::from_str(&text)?)
+    }
+}
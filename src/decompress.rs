@@ -0,0 +1,213 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Compression formats recognised on input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Detect compression from the leading bytes of a stream
+    pub fn from_magic(head: &[u8]) -> Self {
+        if head.starts_with(&[0x1F, 0x8B]) {
+            Compression::Gzip
+        } else if head.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Compression::Zstd
+        } else if Self::is_bzip2(head) {
+            Compression::Bzip2
+        } else if head.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+
+    /// `BZh`, a block size digit, then the magic of the first block or of the
+    /// end of an empty stream, so text that merely starts with "BZh" is not
+    /// mistaken for bzip2
+    fn is_bzip2(head: &[u8]) -> bool {
+        const BLOCK_MAGIC: [u8; 6] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+        const END_MAGIC: [u8; 6] = [0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+        head.len() >= 10
+            && head.starts_with(b"BZh")
+            && (b'1'..=b'9').contains(&head[3])
+            && (head[4..10] == BLOCK_MAGIC || head[4..10] == END_MAGIC)
+    }
+
+    /// Detect compression from a file extension such as `.csv.gz` or `.ndjson.zst`
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Self {
+        let ext = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("gz") | Some("gzip") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            Some("bz2") => Compression::Bzip2,
+            Some("xz") => Compression::Xz,
+            _ => Compression::None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+        }
+    }
+}
+
+/// Bytes needed to recognise every supported format (bzip2 needs 10)
+const MAGIC_LEN: usize = 10;
+
+/// Read the leading bytes of a stream. Pipes may return a few bytes per read,
+/// so keep reading until there are enough to sniff or the input ends.
+fn read_head<R: Read>(input: &mut R) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(MAGIC_LEN);
+    input.by_ref().take(MAGIC_LEN as u64).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Wrap a reader so compressed input is decompressed transparently.
+/// The format is sniffed from the magic bytes; uncompressed input passes
+/// through unchanged. Concatenated members (e.g. `cat a.gz b.gz`) are all read.
+pub fn open_reader<'a, R: Read + 'a>(mut input: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let head = read_head(&mut input)?;
+    let compression = Compression::from_magic(&head);
+    decoder(BufReader::new(io::Cursor::new(head).chain(input)), compression)
+}
+
+/// Open a file for reading, decompressing it if needed. Magic bytes take
+/// precedence; the extension is used when the file is too short to sniff.
+pub fn open_path<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn BufRead>> {
+    let mut file = File::open(&path)?;
+    let head = read_head(&mut file)?;
+    let compression = match Compression::from_magic(&head) {
+        Compression::None if head.len() < MAGIC_LEN => Compression::from_extension(&path),
+        detected => detected,
+    };
+    decoder(BufReader::new(io::Cursor::new(head).chain(file)), compression)
+}
+
+#[cfg(feature = "compression")]
+fn decoder<'a, R: BufRead + 'a>(
+    reader: R,
+    compression: Compression,
+) -> io::Result<Box<dyn BufRead + 'a>> {
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?)),
+        Compression::Bzip2 => Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+        Compression::Xz => Box::new(BufReader::new(xz2::bufread::XzDecoder::new_multi_decoder(reader))),
+    })
+}
+
+#[cfg(not(feature = "compression"))]
+fn decoder<'a, R: BufRead + 'a>(
+    reader: R,
+    compression: Compression,
+) -> io::Result<Box<dyn BufRead + 'a>> {
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} compressed input detected but compression support not enabled. Rebuild with --features compression",
+                other.name()
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection() {
+        assert_eq!(Compression::from_magic(&[0x1F, 0x8B, 0x08]), Compression::Gzip);
+        assert_eq!(Compression::from_magic(&[0x28, 0xB5, 0x2F, 0xFD, 0x00]), Compression::Zstd);
+        assert_eq!(Compression::from_magic(b"BZh91AY&SY\x00"), Compression::Bzip2);
+        assert_eq!(Compression::from_magic(b"\xFD7zXZ\x00\x00"), Compression::Xz);
+        assert_eq!(Compression::from_magic(b"id,name\n"), Compression::None);
+        assert_eq!(Compression::from_extension("landing/2024.csv.gz"), Compression::Gzip);
+        assert_eq!(Compression::from_extension("events.NDJSON.ZST"), Compression::Zstd);
+        assert_eq!(Compression::from_extension("plain.csv"), Compression::None);
+    }
+
+    #[test]
+    fn test_text_starting_with_bzh_is_not_bzip2() {
+        assert_eq!(Compression::from_magic(b"BZh,name\n1,a\n"), Compression::None);
+        assert_eq!(Compression::from_magic(b"BZh9 is a code\n"), Compression::None);
+        assert_eq!(Compression::from_magic(b"BZh9"), Compression::None);
+        let mut text = String::new();
+        open_reader(&b"BZhx|y\n1|2\n"[..]).unwrap().read_to_string(&mut text).unwrap();
+        assert_eq!(text, "BZhx|y\n1|2\n");
+    }
+
+    #[test]
+    fn test_plain_passthrough() {
+        let mut text = String::new();
+        open_reader(&b"a|b\n1|2\n"[..]).unwrap().read_to_string(&mut text).unwrap();
+        assert_eq!(text, "a|b\n1|2\n");
+    }
+
+    #[test]
+    fn test_plain_text_with_compressed_extension_is_read_as_text() {
+        let path = std::env::temp_dir().join(format!("bytefreq_plain_{}.csv.gz", std::process::id()));
+        std::fs::write(&path, "id,name\n1,a\n").unwrap();
+        let mut text = String::new();
+        let result = open_path(&path).and_then(|mut reader| reader.read_to_string(&mut text));
+        std::fs::remove_file(&path).unwrap();
+        result.unwrap();
+        assert_eq!(text, "id,name\n1,a\n");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_multi_member_gzip() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut data = Vec::new();
+        for part in ["a|b\n", "1|2\n"] {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(part.as_bytes()).unwrap();
+            data.extend(encoder.finish().unwrap());
+        }
+        let zstd_data = zstd::encode_all(&b"x\ny\n"[..], 0).unwrap();
+
+        let mut text = String::new();
+        open_reader(&data[..]).unwrap().read_to_string(&mut text).unwrap();
+        assert_eq!(text, "a|b\n1|2\n");
+
+        text.clear();
+        open_reader(&zstd_data[..]).unwrap().read_to_string(&mut text).unwrap();
+        assert_eq!(text, "x\ny\n");
+
+        // A pipe may hand over the magic bytes one at a time
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let n = self.0.len().min(buf.len()).min(1);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+        text.clear();
+        open_reader(Trickle(&data)).unwrap().read_to_string(&mut text).unwrap();
+        assert_eq!(text, "a|b\n1|2\n");
+    }
+}
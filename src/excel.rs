@@ -1,10 +1,203 @@
 #[cfg(feature = "excel")]
-use calamine::{open_workbook_auto, Reader, Range, Data};
+use calamine::{open_workbook_auto, Reader, Range, Data, Sheets, SheetVisible};
+#[cfg(feature = "excel")]
+use quick_xml::events::Event;
+#[cfg(feature = "excel")]
+use std::collections::HashMap;
+use std::collections::HashSet;
+#[cfg(feature = "excel")]
+use std::fs::File;
+#[cfg(feature = "excel")]
+use std::io::{BufReader, Read, Seek};
 use std::path::Path;
 
 #[cfg(feature = "excel")]
 type CellValue = Data;
 
+/// 0-based indices of the hidden rows and hidden columns of a sheet
+#[cfg(feature = "excel")]
+type HiddenRowsAndCols = (Vec<u32>, Vec<u32>);
+
+/// 0-based (row, column) positions of the first and last cell of a region
+pub type CellBounds = ((u32, u32), (u32, u32));
+
+/// The type Excel stored a cell as. Numbers are floats in Excel, so
+/// whole-number values are reported as `Int`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CellType {
+    Int,
+    Float,
+    String,
+    Bool,
+    Date,
+    Duration,
+    Error,
+    Empty,
+}
+
+impl CellType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CellType::Int => "int",
+            CellType::Float => "float",
+            CellType::String => "string",
+            CellType::Bool => "bool",
+            CellType::Date => "date",
+            CellType::Duration => "duration",
+            CellType::Error => "error",
+            CellType::Empty => "empty",
+        }
+    }
+}
+
+/// A cell rendered as text together with the type Excel stored it as.
+/// Error cells render as Excel shows them, e.g. `#N/A` or `#DIV/0!`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExcelCell {
+    pub value: String,
+    pub cell_type: CellType,
+    /// Formula behind the cached value (without the leading `=`), when
+    /// formulas were requested and the cell has one
+    pub formula: Option<String>,
+}
+
+/// Part of a sheet to read instead of its whole used range
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExcelRegion {
+    /// An A1-style range such as `B5:K2000`
+    Range(String),
+    /// A defined Excel Table (ListObject), including its header row. xlsx only
+    Table(String),
+    /// A workbook-level named range
+    NamedRange(String),
+    /// The largest contiguous block of non-empty cells
+    Auto,
+}
+
+/// What to read besides the cached cell values, and from where
+#[derive(Debug, Clone, Default)]
+pub struct ExcelOptions {
+    pub formulas: bool,
+    pub region: Option<ExcelRegion>,
+}
+
+/// Layout summary of one sheet, for the workbook overview
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetOverview {
+    pub name: String,
+    pub visible: bool,
+    /// Used range in A1 notation, e.g. `B2:F40`; `None` for an empty sheet
+    pub dimensions: Option<String>,
+    pub rows: usize,
+    pub cols: usize,
+    /// Likely header row, relative to the used range (as taken by `--header-row`)
+    pub header_row: usize,
+    /// Merged regions in A1 notation
+    pub merged_regions: Vec<String>,
+    /// Hidden rows and columns (0-based sheet positions, xlsx only)
+    pub hidden_rows: Vec<u32>,
+    pub hidden_cols: Vec<u32>,
+    pub error_cells: usize,
+}
+
+/// Column letters for a 0-based column index, e.g. 27 -> `AB`
+pub fn column_letters(col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        letters.push((b'A' + ((n - 1) % 26) as u8) as char);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// A1-style reference for a 0-based (row, column) position, e.g. (4, 1) -> `B5`
+pub fn cell_reference(row: u32, col: u32) -> String {
+    column_letters(col) + &(row + 1).to_string()
+}
+
+/// Parse an A1-style reference such as `B5:K2000`, `$A$1:$C$9` or a single
+/// cell into 0-based (row, column) start and end positions
+pub fn parse_a1_range(reference: &str) -> Result<CellBounds, String> {
+    let cell = |text: &str| -> Result<(u32, u32), String> {
+        let text = text.trim().replace('$', "");
+        let split = text.find(|c: char| c.is_ascii_digit()).unwrap_or(text.len());
+        let (letters, digits) = text.split_at(split);
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("Invalid cell reference '{}'", text));
+        }
+        let row: u32 = digits
+            .parse()
+            .ok()
+            .filter(|r| *r > 0)
+            .ok_or_else(|| format!("Invalid cell reference '{}'", text))?;
+        let col = letters
+            .to_ascii_uppercase()
+            .bytes()
+            .fold(0u32, |acc, b| acc * 26 + (b - b'A' + 1) as u32);
+        Ok((row - 1, col - 1))
+    };
+    let (first, last) = match reference.split_once(':') {
+        Some((first, last)) => (cell(first)?, cell(last)?),
+        None => {
+            let only = cell(reference)?;
+            (only, only)
+        }
+    };
+    Ok((
+        (first.0.min(last.0), first.1.min(last.1)),
+        (first.0.max(last.0), first.1.max(last.1)),
+    ))
+}
+
+/// Bounding box of the largest group of touching (including diagonally)
+/// non-empty cells, given their (row, column) positions
+pub fn largest_block(cells: &[(u32, u32)]) -> Option<CellBounds> {
+    let mut unvisited: HashSet<(u32, u32)> = cells.iter().copied().collect();
+    let mut best: Option<(usize, CellBounds)> = None;
+    for &seed in cells {
+        if !unvisited.remove(&seed) {
+            continue;
+        }
+        let mut stack = vec![seed];
+        let (mut start, mut end) = (seed, seed);
+        let mut size = 0;
+        while let Some((row, col)) = stack.pop() {
+            size += 1;
+            start = (start.0.min(row), start.1.min(col));
+            end = (end.0.max(row), end.1.max(col));
+            for r in row.saturating_sub(1)..=row + 1 {
+                for c in col.saturating_sub(1)..=col + 1 {
+                    if unvisited.remove(&(r, c)) {
+                        stack.push((r, c));
+                    }
+                }
+            }
+        }
+        if !matches!(best, Some((best_size, _)) if best_size >= size) {
+            best = Some((size, (start, end)));
+        }
+    }
+    best.map(|(_, bounds)| bounds)
+}
+
+/// Guess which of the leading rows holds the column headers: the first row
+/// made up only of text cells that spans at least half the widest row.
+/// Title blocks above a table are narrower and so are skipped.
+pub fn guess_header_row(rows: &[Vec<ExcelCell>]) -> usize {
+    let filled = |row: &Vec<ExcelCell>| row.iter().filter(|c| c.cell_type != CellType::Empty).count();
+    let widest = rows.iter().map(filled).max().unwrap_or(0);
+    rows.iter()
+        .take(20)
+        .position(|row| {
+            let count = filled(row);
+            count > 0
+                && count * 2 >= widest
+                && row.iter().all(|c| matches!(c.cell_type, CellType::String | CellType::Empty))
+        })
+        .unwrap_or(0)
+}
+
 #[cfg(feature = "excel")]
 /// Represents an Excel workbook reader
 pub struct ExcelReader;
@@ -18,6 +211,41 @@ impl ExcelReader {
         path: P,
         sheet_index: usize,
     ) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
+        Ok(Self::cells_to_strings(Self::read_cells_by_index(path, sheet_index)?))
+    }
+
+    /// Read data from a specific sheet by name
+    /// Returns a vector of rows, where each row is a vector of strings
+    /// The first row is treated as the header
+    pub fn read_sheet_by_name<P: AsRef<Path>>(
+        path: P,
+        sheet_name: &str,
+    ) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
+        Ok(Self::cells_to_strings(Self::read_cells_by_name(path, sheet_name)?))
+    }
+
+    /// Read a sheet by index (0-based) as typed cells
+    pub fn read_cells_by_index<P: AsRef<Path>>(
+        path: P,
+        sheet_index: usize,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
+        Self::read_cells_by_index_with(path, sheet_index, &ExcelOptions::default())
+    }
+
+    /// Read a sheet by name as typed cells
+    pub fn read_cells_by_name<P: AsRef<Path>>(
+        path: P,
+        sheet_name: &str,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
+        Self::read_cells_by_name_with(path, sheet_name, &ExcelOptions::default())
+    }
+
+    /// Read a sheet by index (0-based) as typed cells, with options
+    pub fn read_cells_by_index_with<P: AsRef<Path>>(
+        path: P,
+        sheet_index: usize,
+        options: &ExcelOptions,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
         let mut workbook = open_workbook_auto(&path)?;
         let sheet_names = workbook.sheet_names().to_vec();
 
@@ -30,27 +258,59 @@ impl ExcelReader {
             .into());
         }
 
-        let sheet_name = &sheet_names[sheet_index];
-        let range = workbook
-            .worksheet_range(sheet_name)
-            .map_err(|e| format!("Error reading sheet '{}': {}", sheet_name, e))?;
-
-        Self::range_to_rows(range)
+        Self::read_sheet(&mut workbook, path.as_ref(), &sheet_names[sheet_index], options)
     }
 
-    /// Read data from a specific sheet by name
-    /// Returns a vector of rows, where each row is a vector of strings
-    /// The first row is treated as the header
-    pub fn read_sheet_by_name<P: AsRef<Path>>(
+    /// Read a sheet by name as typed cells, with options
+    pub fn read_cells_by_name_with<P: AsRef<Path>>(
         path: P,
         sheet_name: &str,
-    ) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
+        options: &ExcelOptions,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
         let mut workbook = open_workbook_auto(&path)?;
-        let range = workbook
+        Self::read_sheet(&mut workbook, path.as_ref(), sheet_name, options)
+    }
+
+    fn read_sheet<RS: Read + Seek>(
+        workbook: &mut Sheets<RS>,
+        path: &Path,
+        sheet_name: &str,
+        options: &ExcelOptions,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
+        // Tables and named ranges carry their own sheet
+        let (sheet_name, bounds) = match &options.region {
+            Some(region) => Self::resolve_region(workbook, path, sheet_name, region)?,
+            None => (sheet_name.to_string(), None),
+        };
+        let sheet_name = sheet_name.as_str();
+        let mut range = workbook
             .worksheet_range(sheet_name)
             .map_err(|e| format!("Error reading sheet '{}': {}", sheet_name, e))?;
+        let bounds = match (bounds, &options.region) {
+            (None, Some(ExcelRegion::Auto)) => {
+                let (start_row, start_col) = range.start().unwrap_or((0, 0));
+                let filled: Vec<(u32, u32)> = range
+                    .used_cells()
+                    .map(|(r, c, _)| (start_row + r as u32, start_col + c as u32))
+                    .collect();
+                largest_block(&filled)
+            }
+            (bounds, _) => bounds,
+        };
+        if let Some((start, end)) = bounds {
+            range = range.range(start, end);
+        }
+        let formulas = if options.formulas {
+            Some(
+                workbook
+                    .worksheet_formula(sheet_name)
+                    .map_err(|e| format!("Error reading formulas of sheet '{}': {}", sheet_name, e))?,
+            )
+        } else {
+            None
+        };
 
-        Self::range_to_rows(range)
+        Self::range_to_rows(range, formulas.as_ref())
     }
 
     /// Get list of sheet names from an Excel file
@@ -61,16 +321,312 @@ impl ExcelReader {
         Ok(workbook.sheet_names().to_vec())
     }
 
-    /// Convert a Range to a vector of rows
-    fn range_to_rows(range: Range<CellValue>) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
+    /// Sheet and cell bounds of the requested region. `Auto` is resolved once
+    /// the sheet's cells are loaded, so it returns no bounds here.
+    fn resolve_region<RS: Read + Seek>(
+        workbook: &mut Sheets<RS>,
+        path: &Path,
+        sheet_name: &str,
+        region: &ExcelRegion,
+    ) -> Result<(String, Option<CellBounds>), Box<dyn std::error::Error>> {
+        match region {
+            ExcelRegion::Range(reference) => Ok((sheet_name.to_string(), Some(parse_a1_range(reference)?))),
+            ExcelRegion::Auto => Ok((sheet_name.to_string(), None)),
+            ExcelRegion::Table(name) => {
+                let xlsx = match workbook {
+                    Sheets::Xlsx(xlsx) => xlsx,
+                    _ => return Err("Excel tables can only be read from xlsx files".into()),
+                };
+                xlsx.load_tables()?;
+                let table = xlsx.table_by_name(name).map_err(|_| {
+                    let known: Vec<&str> = xlsx.table_names().into_iter().map(|n| n.as_str()).collect();
+                    format!("Table '{}' not found. Tables: {}", name, known.join(", "))
+                })?;
+                let (start, end) = match (table.data().start(), table.data().end()) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => return Err(format!("Table '{}' has no data rows", name).into()),
+                };
+                // The data range excludes the header row just above it, if
+                // the table has one; calamine does not expose the flag
+                let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?))?;
+                let start = if Self::xlsx_table_has_header(&mut archive, name)? {
+                    (start.0.saturating_sub(1), start.1)
+                } else {
+                    start
+                };
+                Ok((table.sheet_name().to_string(), Some((start, end))))
+            }
+            ExcelRegion::NamedRange(name) => {
+                let formula = workbook
+                    .defined_names()
+                    .iter()
+                    .find(|(defined, _)| defined.eq_ignore_ascii_case(name))
+                    .map(|(_, formula)| formula.clone())
+                    .ok_or_else(|| format!("Named range '{}' not found", name))?;
+                let (sheet, reference) = formula
+                    .rsplit_once('!')
+                    .ok_or_else(|| format!("Named range '{}' ({}) does not refer to a sheet", name, formula))?;
+                if reference.contains(',') {
+                    return Err(format!("Named range '{}' ({}) covers more than one area", name, formula).into());
+                }
+                let sheet = sheet.trim_start_matches('=');
+                let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+                    Some(quoted) => quoted.replace("''", "'"),
+                    None => sheet.to_string(),
+                };
+                Ok((sheet, Some(parse_a1_range(reference)?)))
+            }
+        }
+    }
+
+    /// Names of the sheets matching a glob such as `Sales*` or `20??`;
+    /// every sheet when no pattern is given
+    pub fn matching_sheet_names<P: AsRef<Path>>(
+        path: P,
+        pattern: Option<&str>,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let names = Self::get_sheet_names(path)?;
+        match pattern {
+            Some(pattern) => {
+                let matcher = globset::Glob::new(pattern)?.compile_matcher();
+                Ok(names.into_iter().filter(|name| matcher.is_match(name)).collect())
+            }
+            None => Ok(names),
+        }
+    }
+
+    /// Summarise the layout of every sheet: used range, header row guess,
+    /// merged regions (xlsx and xls only), hidden rows and columns (xlsx only)
+    /// and the number of error cells
+    pub fn workbook_overview<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Vec<SheetOverview>, Box<dyn std::error::Error>> {
+        Self::overview(path, None)
+    }
+
+    /// Layout summary of a single sheet
+    pub fn sheet_overview<P: AsRef<Path>>(
+        path: P,
+        sheet_name: &str,
+    ) -> Result<SheetOverview, Box<dyn std::error::Error>> {
+        Self::overview(path, Some(sheet_name))?
+            .pop()
+            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name).into())
+    }
+
+    fn overview<P: AsRef<Path>>(
+        path: P,
+        only: Option<&str>,
+    ) -> Result<Vec<SheetOverview>, Box<dyn std::error::Error>> {
+        let mut workbook = open_workbook_auto(&path)?;
+        let mut hidden = HashMap::new();
+        if let Sheets::Xlsx(xlsx) = &mut workbook {
+            xlsx.load_merged_regions()?;
+            hidden = Self::xlsx_hidden_rows_and_cols(&path)?;
+        }
+        let sheets: Vec<_> = workbook
+            .sheets_metadata()
+            .iter()
+            .filter(|sheet| only.is_none() || only == Some(sheet.name.as_str()))
+            .cloned()
+            .collect();
+
+        let mut overview = Vec::new();
+        for sheet in sheets {
+            let range = workbook
+                .worksheet_range(&sheet.name)
+                .map_err(|e| format!("Error reading sheet '{}': {}", sheet.name, e))?;
+            let merged = match &workbook {
+                Sheets::Xlsx(xlsx) => xlsx
+                    .merged_regions_by_sheet(&sheet.name)
+                    .into_iter()
+                    .map(|(_, _, dims)| *dims)
+                    .collect(),
+                Sheets::Xls(xls) => xls.worksheet_merge_cells(&sheet.name).unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            let dimensions = match (range.start(), range.end()) {
+                (Some(start), Some(end)) => Some(format!(
+                    "{}:{}",
+                    cell_reference(start.0, start.1),
+                    cell_reference(end.0, end.1)
+                )),
+                _ => None,
+            };
+            let error_cells = range.used_cells().filter(|(_, _, c)| matches!(c, CellValue::Error(_))).count();
+            let (rows, cols) = range.get_size();
+            let header_row = Self::range_to_rows(range, None).map(|rows| guess_header_row(&rows)).unwrap_or(0);
+            let (hidden_rows, hidden_cols) = hidden.remove(&sheet.name).unwrap_or_default();
+
+            overview.push(SheetOverview {
+                name: sheet.name,
+                visible: sheet.visible == SheetVisible::Visible,
+                dimensions,
+                rows,
+                cols,
+                header_row,
+                merged_regions: merged
+                    .iter()
+                    .map(|d| format!("{}:{}", cell_reference(d.start.0, d.start.1), cell_reference(d.end.0, d.end.1)))
+                    .collect(),
+                hidden_rows,
+                hidden_cols,
+                error_cells,
+            });
+        }
+        Ok(overview)
+    }
+
+    /// Hidden rows and columns per sheet name. calamine does not expose
+    /// row and column visibility, so the worksheet XML is read directly.
+    fn xlsx_hidden_rows_and_cols<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<HashMap<String, HiddenRowsAndCols>, Box<dyn std::error::Error>> {
+        let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?))?;
+
+        // Sheet name -> relationship id, then relationship id -> part name
+        let mut sheet_ids = Vec::new();
+        Self::scan_xml(&mut archive, "xl/workbook.xml", |name, attrs| {
+            if name == "sheet" {
+                if let (Some(sheet), Some(id)) = (attrs.get("name"), attrs.get("id")) {
+                    sheet_ids.push((sheet.clone(), id.clone()));
+                }
+            }
+        })?;
+        let mut targets = HashMap::new();
+        Self::scan_xml(&mut archive, "xl/_rels/workbook.xml.rels", |name, attrs| {
+            if name == "Relationship" {
+                if let (Some(id), Some(target)) = (attrs.get("Id"), attrs.get("Target")) {
+                    let part = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    targets.insert(id.clone(), part);
+                }
+            }
+        })?;
+
+        let mut hidden = HashMap::new();
+        for (sheet, id) in sheet_ids {
+            let part = match targets.get(&id) {
+                Some(part) => part,
+                None => continue,
+            };
+            let mut rows = Vec::new();
+            let mut cols = Vec::new();
+            let is_set = |value: Option<&String>| matches!(value.map(|v| v.as_str()), Some("1") | Some("true"));
+            Self::scan_xml(&mut archive, part, |name, attrs| {
+                if name == "row" && is_set(attrs.get("hidden")) {
+                    if let Some(r) = attrs.get("r").and_then(|r| r.parse::<u32>().ok()) {
+                        rows.push(r - 1);
+                    }
+                } else if name == "col" && is_set(attrs.get("hidden")) {
+                    let min = attrs.get("min").and_then(|v| v.parse::<u32>().ok());
+                    let max = attrs.get("max").and_then(|v| v.parse::<u32>().ok());
+                    if let (Some(min), Some(max)) = (min, max) {
+                        cols.extend(min - 1..max);
+                    }
+                }
+            })?;
+            hidden.insert(sheet, (rows, cols));
+        }
+        Ok(hidden)
+    }
+
+    /// Whether a table has a header row. Excel writes `headerRowCount="0"`
+    /// on tables without one and leaves the attribute out otherwise.
+    fn xlsx_table_has_header<R: Read + Seek>(
+        archive: &mut zip::ZipArchive<R>,
+        table_name: &str,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let parts: Vec<String> = archive
+            .file_names()
+            .filter(|part| part.starts_with("xl/tables/") && part.ends_with(".xml"))
+            .map(String::from)
+            .collect();
+        let mut has_header = None;
+        for part in parts {
+            Self::scan_xml(archive, &part, |name, attrs| {
+                let named = [attrs.get("displayName"), attrs.get("name")]
+                    .iter()
+                    .any(|n| n.map(String::as_str) == Some(table_name));
+                if name == "table" && named {
+                    has_header = Some(attrs.get("headerRowCount").map(String::as_str) != Some("0"));
+                }
+            })?;
+            if let Some(has_header) = has_header {
+                return Ok(has_header);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Call `visit` with the local name and attributes (keyed by local name)
+    /// of every element in one part of the xlsx archive
+    fn scan_xml<R, F>(
+        archive: &mut zip::ZipArchive<R>,
+        part: &str,
+        mut visit: F,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        R: Read + Seek,
+        F: FnMut(&str, &HashMap<String, String>),
+    {
+        let file = match archive.by_name(part) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut reader = quick_xml::Reader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) | Event::Empty(e) => {
+                    let mut attrs = HashMap::new();
+                    for attr in e.attributes().flatten() {
+                        let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+                        attrs.insert(key, attr.decode_and_unescape_value(&reader)?.into_owned());
+                    }
+                    visit(&String::from_utf8_lossy(e.local_name().as_ref()), &attrs);
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(())
+    }
+
+    fn cells_to_strings(rows: Vec<Vec<ExcelCell>>) -> Vec<Vec<String>> {
+        rows.into_iter()
+            .map(|row| row.into_iter().map(|cell| cell.value).collect())
+            .collect()
+    }
+
+    /// Convert a Range to a vector of rows, attaching formulas when given.
+    /// The formula range can cover a different area, so cells are matched
+    /// on their absolute positions.
+    fn range_to_rows(
+        range: Range<CellValue>,
+        formulas: Option<&Range<String>>,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
         let mut rows = Vec::new();
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
 
-        for row in range.rows() {
-            let string_row: Vec<String> = row
+        for (r, row) in range.rows().enumerate() {
+            let cell_row: Vec<ExcelCell> = row
                 .iter()
-                .map(|cell| Self::cell_to_string(cell))
+                .enumerate()
+                .map(|(c, cell)| ExcelCell {
+                    value: Self::cell_to_string(cell),
+                    cell_type: Self::cell_type(cell),
+                    formula: formulas
+                        .and_then(|f| f.get_value((start_row + r as u32, start_col + c as u32)))
+                        .filter(|f| !f.is_empty())
+                        .cloned(),
+                })
                 .collect();
-            rows.push(string_row);
+            rows.push(cell_row);
         }
 
         if rows.is_empty() {
@@ -95,17 +651,48 @@ impl ExcelReader {
             CellValue::String(s) => s.clone(),
             CellValue::Bool(b) => b.to_string(),
             CellValue::DateTime(dt) => {
-                // Excel dates are stored as days since 1900-01-01
-                // For now, just convert to string representation
-                dt.to_string()
+                // Excel stores dates as serial day numbers from the workbook's
+                // 1900 or 1904 epoch; calamine applies the right one
+                if dt.is_duration() {
+                    match dt.as_duration() {
+                        Some(d) => format!(
+                            "{}:{:02}:{:02}",
+                            d.num_hours(),
+                            d.num_minutes() % 60,
+                            d.num_seconds() % 60
+                        ),
+                        None => dt.to_string(),
+                    }
+                } else {
+                    match dt.as_datetime() {
+                        Some(d) if d.time() == chrono::NaiveTime::MIN => d.format("%Y-%m-%d").to_string(),
+                        Some(d) => d.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+                        None => dt.to_string(),
+                    }
+                }
             }
             CellValue::DateTimeIso(s) => s.clone(),
             CellValue::DurationIso(s) => s.clone(),
-            CellValue::Error(e) => format!("#ERROR:{:?}", e),
+            CellValue::Error(e) => e.to_string(),
             CellValue::Empty => String::new(),
         }
     }
 
+    fn cell_type(cell: &CellValue) -> CellType {
+        match cell {
+            CellValue::Int(_) => CellType::Int,
+            CellValue::Float(f) if f.fract() == 0.0 && f.is_finite() => CellType::Int,
+            CellValue::Float(_) => CellType::Float,
+            CellValue::String(_) => CellType::String,
+            CellValue::Bool(_) => CellType::Bool,
+            CellValue::DateTime(dt) if dt.is_duration() => CellType::Duration,
+            CellValue::DateTime(_) | CellValue::DateTimeIso(_) => CellType::Date,
+            CellValue::DurationIso(_) => CellType::Duration,
+            CellValue::Error(_) => CellType::Error,
+            CellValue::Empty => CellType::Empty,
+        }
+    }
+
     /// Read the first sheet from an Excel file
     pub fn read_first_sheet<P: AsRef<Path>>(
         path: P,
@@ -134,6 +721,20 @@ impl ExcelReader {
         Err("Excel support not enabled. Rebuild with --features excel".into())
     }
 
+    pub fn read_cells_by_index<P: AsRef<Path>>(
+        _path: P,
+        _sheet_index: usize,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
+        Err("Excel support not enabled. Rebuild with --features excel".into())
+    }
+
+    pub fn read_cells_by_name<P: AsRef<Path>>(
+        _path: P,
+        _sheet_name: &str,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
+        Err("Excel support not enabled. Rebuild with --features excel".into())
+    }
+
     pub fn read_first_sheet<P: AsRef<Path>>(
         _path: P,
     ) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
@@ -145,6 +746,42 @@ impl ExcelReader {
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         Err("Excel support not enabled. Rebuild with --features excel".into())
     }
+
+    pub fn read_cells_by_index_with<P: AsRef<Path>>(
+        _path: P,
+        _sheet_index: usize,
+        _options: &ExcelOptions,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
+        Err("Excel support not enabled. Rebuild with --features excel".into())
+    }
+
+    pub fn read_cells_by_name_with<P: AsRef<Path>>(
+        _path: P,
+        _sheet_name: &str,
+        _options: &ExcelOptions,
+    ) -> Result<Vec<Vec<ExcelCell>>, Box<dyn std::error::Error>> {
+        Err("Excel support not enabled. Rebuild with --features excel".into())
+    }
+
+    pub fn sheet_overview<P: AsRef<Path>>(
+        _path: P,
+        _sheet_name: &str,
+    ) -> Result<SheetOverview, Box<dyn std::error::Error>> {
+        Err("Excel support not enabled. Rebuild with --features excel".into())
+    }
+
+    pub fn matching_sheet_names<P: AsRef<Path>>(
+        _path: P,
+        _pattern: Option<&str>,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Err("Excel support not enabled. Rebuild with --features excel".into())
+    }
+
+    pub fn workbook_overview<P: AsRef<Path>>(
+        _path: P,
+    ) -> Result<Vec<SheetOverview>, Box<dyn std::error::Error>> {
+        Err("Excel support not enabled. Rebuild with --features excel".into())
+    }
 }
 
 #[cfg(test)]
@@ -163,5 +800,102 @@ mod tests {
         );
         assert_eq!(ExcelReader::cell_to_string(&CellValue::Bool(true)), "true");
         assert_eq!(ExcelReader::cell_to_string(&CellValue::Empty), "");
+        assert_eq!(ExcelReader::cell_to_string(&CellValue::Error(calamine::CellErrorType::NA)), "#N/A");
+        assert_eq!(ExcelReader::cell_to_string(&CellValue::Error(calamine::CellErrorType::Div0)), "#DIV/0!");
+    }
+
+    #[test]
+    fn test_formulas_follow_absolute_positions() {
+        let mut values: Range<CellValue> = Range::new((1, 1), (2, 2));
+        values.set_value((1, 1), CellValue::Int(2));
+        values.set_value((2, 2), CellValue::Error(calamine::CellErrorType::Ref));
+        let mut formulas: Range<String> = Range::new((2, 2), (2, 2));
+        formulas.set_value((2, 2), "#REF!*2".to_string());
+
+        let rows = ExcelReader::range_to_rows(values, Some(&formulas)).unwrap();
+        assert_eq!(rows[0][0].formula, None);
+        assert_eq!(rows[1][1].value, "#REF!");
+        assert_eq!(rows[1][1].cell_type, CellType::Error);
+        assert_eq!(rows[1][1].formula.as_deref(), Some("#REF!*2"));
+    }
+
+    #[test]
+    fn test_dates_and_cell_types() {
+        use calamine::{ExcelDateTime, ExcelDateTimeType};
+
+        let date_1900 = CellValue::DateTime(ExcelDateTime::new(42369.0, ExcelDateTimeType::DateTime, false));
+        let date_1904 = CellValue::DateTime(ExcelDateTime::new(40907.0, ExcelDateTimeType::DateTime, true));
+        let stamp = CellValue::DateTime(ExcelDateTime::new(42369.5, ExcelDateTimeType::DateTime, false));
+        let duration = CellValue::DateTime(ExcelDateTime::new(1.5, ExcelDateTimeType::TimeDelta, false));
+        assert_eq!(ExcelReader::cell_to_string(&date_1900), "2015-12-31");
+        assert_eq!(ExcelReader::cell_to_string(&date_1904), "2015-12-31");
+        assert_eq!(ExcelReader::cell_to_string(&stamp), "2015-12-31T12:00:00");
+        assert_eq!(ExcelReader::cell_to_string(&duration), "36:00:00");
+
+        assert_eq!(ExcelReader::cell_type(&date_1900), CellType::Date);
+        assert_eq!(ExcelReader::cell_type(&duration), CellType::Duration);
+        assert_eq!(ExcelReader::cell_type(&CellValue::Float(3.0)), CellType::Int);
+        assert_eq!(ExcelReader::cell_type(&CellValue::Float(3.5)), CellType::Float);
+        assert_eq!(ExcelReader::cell_type(&CellValue::String("42".into())), CellType::String);
+    }
+
+    #[test]
+    fn test_layout_helpers() {
+        assert_eq!(cell_reference(0, 0), "A1");
+        assert_eq!(cell_reference(4, 1), "B5");
+        assert_eq!(cell_reference(9, 26), "AA10");
+        assert_eq!(cell_reference(0, 701), "ZZ1");
+
+        let cell = |v: &str, cell_type| ExcelCell { value: v.to_string(), cell_type, formula: None };
+        let text = |v: &str| cell(v, CellType::String);
+        let empty = || cell("", CellType::Empty);
+        let int = |v: &str| cell(v, CellType::Int);
+        let rows = vec![
+            vec![text("Quarterly sales"), empty(), empty()],
+            vec![empty(), empty(), empty()],
+            vec![text("region"), text("sales"), text("units")],
+            vec![text("North"), int("100"), int("7")],
+        ];
+        assert_eq!(guess_header_row(&rows), 2);
+        assert_eq!(guess_header_row(&rows[3..]), 0);
+    }
+
+    #[test]
+    fn test_regions() {
+        assert_eq!(parse_a1_range("B5:K2000"), Ok(((4, 1), (1999, 10))));
+        assert_eq!(parse_a1_range("$D$9:$b$6"), Ok(((5, 1), (8, 3))));
+        assert_eq!(parse_a1_range("AA10"), Ok(((9, 26), (9, 26))));
+        assert!(parse_a1_range("5B").is_err());
+        assert!(parse_a1_range("A0").is_err());
+
+        // A title cell, then a 3x3 table with a gap, then a stray note
+        let cells = vec![
+            (0, 0),
+            (3, 1), (3, 2), (3, 3),
+            (4, 1), (4, 3),
+            (5, 1), (5, 2), (5, 3),
+            (9, 0),
+        ];
+        assert_eq!(largest_block(&cells), Some(((3, 1), (5, 3))));
+        assert_eq!(largest_block(&[]), None);
+    }
+
+    #[test]
+    fn test_table_header_row_flag() {
+        use std::io::{Cursor, Write};
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let tables = [
+            ("xl/tables/table1.xml", r#"<table xmlns="x" id="1" name="Sales" displayName="Sales" ref="A1:B3"/>"#),
+            ("xl/tables/table2.xml", r#"<table xmlns="x" id="2" name="Raw" displayName="Raw" ref="D5:E7" headerRowCount="0"/>"#),
+        ];
+        for (part, xml) in tables {
+            writer.start_file(part, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(xml.as_bytes()).unwrap();
+        }
+        let mut archive = zip::ZipArchive::new(writer.finish().unwrap()).unwrap();
+
+        assert!(ExcelReader::xlsx_table_has_header(&mut archive, "Sales").unwrap());
+        assert!(!ExcelReader::xlsx_table_has_header(&mut archive, "Raw").unwrap());
     }
 }
@@ -0,0 +1,556 @@
+// Expectations about a delivery, checked against the profile built by the DQ
+// pass so bytefreq can act as a gate in a pipeline. They can be written by
+// hand or learned from a file known to be good.
+
+use crate::config::load_config;
+use crate::rules::scorecard::Scorecard;
+use crate::types::InferredType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// Expectations loaded from YAML, or TOML for files ending in `.toml`.
+/// Rates are fractions between 0 and 1.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    /// Grain the masks were written in, overriding `--grain`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grain: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_columns: Vec<String>,
+    /// Share of rows allowed more or fewer fields than the header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ragged_rate: Option<f64>,
+    #[serde(default)]
+    pub columns: BTreeMap<String, ColumnExpectation>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnExpectation {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<DataType>,
+    /// Share of values allowed to be empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_null_rate: Option<f64>,
+    /// Length in characters of non-empty values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<LengthRange>,
+    /// Masks the column may contain; not checked when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_masks: Option<Vec<String>>,
+    /// Share of values allowed outside `allowed_masks` (default 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_unexpected_rate: Option<f64>,
+    /// Minimum pass rate per scorecard check, e.g. `is_uk_postcode: 0.95`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub min_pass_rates: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LengthRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+}
+
+/// Column type, from the same inference as `--types`. An integer column
+/// also satisfies `decimal`, and anything satisfies `string`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataType {
+    Integer,
+    Decimal,
+    Boolean,
+    Date,
+    Datetime,
+    String,
+}
+
+impl DataType {
+    /// The expectation type for a type recommended by `TypeProfile`. Enums
+    /// and identifiers are kept as text, so they become strings.
+    pub fn of_inferred(inferred: &InferredType) -> DataType {
+        match inferred {
+            InferredType::Integer { .. } => DataType::Integer,
+            InferredType::Decimal { .. } => DataType::Decimal,
+            InferredType::Boolean => DataType::Boolean,
+            InferredType::Date { .. } => DataType::Date,
+            InferredType::Datetime { .. } => DataType::Datetime,
+            InferredType::Enum { .. } | InferredType::Identifier | InferredType::Text => DataType::String,
+        }
+    }
+
+    fn satisfies(self, expected: DataType) -> bool {
+        self == expected || expected == DataType::String || (self == DataType::Integer && expected == DataType::Decimal)
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            DataType::Integer => "integer",
+            DataType::Decimal => "decimal",
+            DataType::Boolean => "boolean",
+            DataType::Date => "date",
+            DataType::Datetime => "datetime",
+            DataType::String => "string",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Null and length counts of the raw values in one column
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueStats {
+    pub values: usize,
+    /// Values that are empty or only whitespace
+    pub nulls: usize,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+impl ValueStats {
+    pub fn observe(&mut self, raw: &str) {
+        self.values += 1;
+        if raw.trim().is_empty() {
+            self.nulls += 1;
+            return;
+        }
+        let length = raw.chars().count();
+        self.min_length = Some(self.min_length.map_or(length, |min| min.min(length)));
+        self.max_length = Some(self.max_length.map_or(length, |max| max.max(length)));
+    }
+}
+
+/// Null and length counts per column, gathered a record at a time so that
+/// a column missing from some records (an optional JSON key, or the tail of
+/// a short ragged row) counts as empty there
+#[derive(Debug, Default)]
+pub struct RecordStats {
+    pub rows: usize,
+    columns: HashMap<String, ValueStats>,
+    /// Records holding at least one value per column
+    present: HashMap<String, usize>,
+}
+
+impl RecordStats {
+    pub fn new() -> Self {
+        RecordStats::default()
+    }
+
+    /// Observe one record's values by column. A column may repeat, e.g. a
+    /// JSON array path with the array numbers removed.
+    pub fn observe<'a>(&mut self, fields: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        self.rows += 1;
+        let mut seen = HashSet::new();
+        for (column, raw) in fields {
+            if seen.insert(column) {
+                *self.present.entry(column.to_string()).or_insert(0) += 1;
+            }
+            self.columns.entry(column.to_string()).or_default().observe(raw);
+        }
+    }
+
+    /// Statistics for a column, counting the records without it as nulls
+    pub fn column(&self, name: &str) -> ValueStats {
+        let mut stats = self.columns.get(name).cloned().unwrap_or_default();
+        let missing = self.rows.saturating_sub(self.present.get(name).copied().unwrap_or(0));
+        stats.values += missing;
+        stats.nulls += missing;
+        stats
+    }
+}
+
+/// Mask counts, value statistics and inferred type of one column from the DQ pass
+#[derive(Debug, Clone)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub masks: HashMap<String, usize>,
+    pub stats: ValueStats,
+    /// Type recommended by `TypeProfile`; None when every value was empty
+    pub inferred: Option<InferredType>,
+}
+
+/// What the DQ pass saw, reduced to what expectations are checked against
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub rows: usize,
+    /// Rows with more or fewer fields than the header
+    pub ragged_rows: usize,
+    pub columns: Vec<ColumnProfile>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// None for file level checks
+    pub column: Option<String>,
+    pub check: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<32}\t{:<24}\t{}", self.column.as_deref().unwrap_or("-"), self.check, self.message)
+    }
+}
+
+/// The outcome of checking a profile
+#[derive(Debug, Default)]
+pub struct Validation {
+    pub checks: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl Validation {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    fn check(&mut self, column: Option<&str>, check: &str, failure: Option<String>) {
+        self.checks += 1;
+        if let Some(message) = failure {
+            self.violations.push(Violation {
+                column: column.map(|c| c.to_string()),
+                check: check.to_string(),
+                message,
+            });
+        }
+    }
+}
+
+impl Expectations {
+    /// Load expectations from a `.toml` file, or YAML for any other extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        load_config(path)
+    }
+
+    /// Expectations describing a profile known to be good. Masks seen in less
+    /// than `min_mask_share` of a column's values are left out of
+    /// `allowed_masks`, and their combined share becomes the column's
+    /// `max_unexpected_rate`. Observed rates are rounded up.
+    pub fn learn(profile: &Profile, grain: &str, min_mask_share: f64) -> Self {
+        let mut columns = BTreeMap::new();
+        for column in profile.columns.iter().filter(|column| !column.name.starts_with("RaggedErr")) {
+            let total: usize = column.masks.values().sum();
+            let mut masks: Vec<(&String, &usize)> = column.masks.iter().collect();
+            masks.sort_unstable_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            let (allowed, rare): (Vec<_>, Vec<_>) =
+                masks.into_iter().partition(|(_, count)| share(**count, total) >= min_mask_share);
+            let rare_count: usize = rare.iter().map(|(_, count)| **count).sum();
+
+            let stats = &column.stats;
+            let length = match (stats.min_length, stats.max_length) {
+                (None, None) => None,
+                (min, max) => Some(LengthRange { min, max }),
+            };
+            columns.insert(
+                column.name.clone(),
+                ColumnExpectation {
+                    data_type: column.inferred.as_ref().map(DataType::of_inferred),
+                    max_null_rate: Some(round_up(share(stats.nulls, stats.values))),
+                    length,
+                    allowed_masks: Some(allowed.into_iter().map(|(mask, _)| mask.clone()).collect()),
+                    max_unexpected_rate: if rare_count > 0 { Some(round_up(share(rare_count, total))) } else { None },
+                    min_pass_rates: BTreeMap::new(),
+                },
+            );
+        }
+
+        Expectations {
+            grain: Some(grain.to_string()),
+            required_columns: columns.keys().cloned().collect(),
+            max_ragged_rate: Some(round_up(share(profile.ragged_rows, profile.rows))),
+            columns,
+        }
+    }
+
+    /// Whether any check needs assertion results, i.e. a scorecard
+    pub fn needs_scorecard(&self) -> bool {
+        self.columns.values().any(|column| !column.min_pass_rates.is_empty())
+    }
+
+    /// Check the profile, and the scorecard for pass rates
+    pub fn validate(&self, profile: &Profile, scorecard: Option<&Scorecard>) -> Validation {
+        let mut validation = Validation::default();
+        let columns: HashMap<&str, &ColumnProfile> =
+            profile.columns.iter().map(|column| (column.name.as_str(), column)).collect();
+
+        for name in &self.required_columns {
+            let failure = if columns.contains_key(name.as_str()) {
+                None
+            } else {
+                Some("column is missing".to_string())
+            };
+            validation.check(Some(name), "required", failure);
+        }
+
+        if let Some(max) = self.max_ragged_rate {
+            let rate = share(profile.ragged_rows, profile.rows);
+            let failure = if rate > max {
+                Some(format!("{} of {} rows ragged ({}), max {}", profile.ragged_rows, profile.rows, percent(rate), percent(max)))
+            } else {
+                None
+            };
+            validation.check(None, "max_ragged_rate", failure);
+        }
+
+        for (name, expectation) in &self.columns {
+            // Missing columns are only reported through required_columns
+            let column = match columns.get(name.as_str()) {
+                Some(column) => column,
+                None => continue,
+            };
+
+            if let Some(expected) = expectation.data_type {
+                let failure = match column.inferred.as_ref().map(DataType::of_inferred) {
+                    Some(seen) if !seen.satisfies(expected) => Some(format!("expected {}, values look like {}", expected, seen)),
+                    _ => None,
+                };
+                validation.check(Some(name), "type", failure);
+            }
+
+            if let Some(max) = expectation.max_null_rate {
+                let stats = &column.stats;
+                let rate = share(stats.nulls, stats.values);
+                let failure = if rate > max {
+                    Some(format!("{} of {} values empty ({}), max {}", stats.nulls, stats.values, percent(rate), percent(max)))
+                } else {
+                    None
+                };
+                validation.check(Some(name), "max_null_rate", failure);
+            }
+
+            if let Some(range) = expectation.length {
+                let stats = &column.stats;
+                let too_short = matches!((range.min, stats.min_length), (Some(min), Some(seen)) if seen < min);
+                let too_long = matches!((range.max, stats.max_length), (Some(max), Some(seen)) if seen > max);
+                let failure = if too_short || too_long {
+                    let bound = |b: Option<usize>| b.map_or("-".to_string(), |b| b.to_string());
+                    Some(format!(
+                        "lengths {}..{}, expected {}..{}",
+                        bound(stats.min_length),
+                        bound(stats.max_length),
+                        bound(range.min),
+                        bound(range.max)
+                    ))
+                } else {
+                    None
+                };
+                validation.check(Some(name), "length", failure);
+            }
+
+            if let Some(allowed) = &expectation.allowed_masks {
+                let total: usize = column.masks.values().sum();
+                let mut unexpected: Vec<(&String, &usize)> =
+                    column.masks.iter().filter(|(mask, _)| !allowed.contains(mask)).collect();
+                unexpected.sort_unstable_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+                let count: usize = unexpected.iter().map(|(_, count)| **count).sum();
+                let rate = share(count, total);
+                let max = expectation.max_unexpected_rate.unwrap_or(0.0);
+                let failure = if rate > max {
+                    let masks: Vec<String> = unexpected
+                        .iter()
+                        .take(5)
+                        .map(|(mask, count)| format!("'{}' x{}", mask, count))
+                        .collect();
+                    Some(format!("{} of {} values unexpected ({}), max {}: {}", count, total, percent(rate), percent(max), masks.join(", ")))
+                } else {
+                    None
+                };
+                validation.check(Some(name), "allowed_masks", failure);
+            }
+
+            for (check, min) in &expectation.min_pass_rates {
+                let rate = scorecard.and_then(|scorecard| pass_rate(scorecard, name, check));
+                let failure = match rate {
+                    Some(rate) if rate >= *min => None,
+                    Some(rate) => Some(format!("{} passed {}, min {}", check, percent(rate), percent(*min))),
+                    None => Some(format!("{} did not run on this column", check)),
+                };
+                validation.check(Some(name), "min_pass_rates", failure);
+            }
+        }
+
+        validation
+    }
+}
+
+/// Pass rate of a scorecard check named `rule` or `rule.key`, as a fraction
+fn pass_rate(scorecard: &Scorecard, column: &str, check: &str) -> Option<f64> {
+    let (rule, key) = match check.split_once('.') {
+        Some((rule, key)) => (rule, key),
+        None => (check, check),
+    };
+    let score = scorecard.column(column)?.get(rule)?;
+    let rate = score.keys.get(key).map_or(0.0, |key_score| key_score.rate(score.applied));
+    Some(rate / 100.0)
+}
+
+fn share(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Round a rate up to four decimal places, so a learned limit is never
+/// tighter than what was observed
+fn round_up(rate: f64) -> f64 {
+    (rate * 10000.0).ceil() / 10000.0
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.1}%", rate * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::registry::RuleRegistry;
+
+    fn column(name: &str, masks: &[(&str, usize)]) -> ColumnProfile {
+        ColumnProfile {
+            name: name.to_string(),
+            masks: masks.iter().map(|(mask, count)| (mask.to_string(), *count)).collect(),
+            stats: ValueStats::default(),
+            inferred: None,
+        }
+    }
+
+    fn failed(validation: &Validation, check: &str) -> Vec<Option<String>> {
+        validation
+            .violations
+            .iter()
+            .filter(|v| v.check == check)
+            .map(|v| v.column.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_profile() {
+        let expectations: Expectations = serde_yaml::from_str(
+            r#"
+required_columns: [id, postcode, email]
+max_ragged_rate: 0.1
+columns:
+  id:
+    allowed_masks: ["9"]
+  postcode:
+    allowed_masks: ["A9 9A"]
+    max_unexpected_rate: 0.5
+    min_pass_rates: { is_uk_postcode: 0.9 }
+"#,
+        )
+        .unwrap();
+        assert!(expectations.needs_scorecard());
+
+        let profile = Profile {
+            rows: 4,
+            ragged_rows: 1,
+            columns: vec![column("id", &[("9", 3), ("A9", 1)]), column("postcode", &[("A9 9A", 3), ("9", 1)])],
+        };
+        let registry = RuleRegistry::with_builtin_rules();
+        let mut scorecard = Scorecard::new();
+        for raw in ["M1 1AE", "ZZ9 9ZZ"] {
+            scorecard.observe("postcode", raw, &registry.evaluate_each("postcode", raw, "A9 9A", "A9 9AA"));
+        }
+        let validation = expectations.validate(&profile, Some(&scorecard));
+
+        assert_eq!(validation.checks, 7);
+        assert_eq!(failed(&validation, "required"), vec![Some("email".to_string())]);
+        assert_eq!(failed(&validation, "max_ragged_rate"), vec![None]);
+        // postcode's 25% unexpected masks are within its 50% allowance
+        assert_eq!(failed(&validation, "allowed_masks"), vec![Some("id".to_string())]);
+        let violation = validation.violations.iter().find(|v| v.check == "allowed_masks").unwrap();
+        assert!(violation.message.contains("'A9' x1"));
+        assert_eq!(failed(&validation, "min_pass_rates"), vec![Some("postcode".to_string())]);
+    }
+
+    #[test]
+    fn test_json_records_are_rows_and_absent_fields_are_nulls() {
+        let mut stats = RecordStats::new();
+        stats.observe(vec![("id", "1"), ("email", "x@example.com"), ("tags[]", "a"), ("tags[]", "b")]);
+        for id in ["2", "3", "4"] {
+            stats.observe(vec![("id", id)]);
+        }
+        assert_eq!(stats.rows, 4);
+        assert_eq!(stats.column("id").values, 4);
+        // Array elements are values too, and each record without the array adds a null
+        assert_eq!((stats.column("tags[]").values, stats.column("tags[]").nulls), (5, 3));
+
+        let expectations: Expectations =
+            serde_yaml::from_str("columns:\n  email:\n    max_null_rate: 0.5\n").unwrap();
+        let profile = Profile {
+            rows: stats.rows,
+            ragged_rows: 0,
+            columns: vec![ColumnProfile {
+                name: "email".to_string(),
+                masks: HashMap::new(),
+                stats: stats.column("email"),
+                inferred: Some(InferredType::Text),
+            }],
+        };
+        let validation = expectations.validate(&profile, None);
+        assert_eq!(validation.violations.len(), 1);
+        assert_eq!(validation.violations[0].message, "3 of 4 values empty (75.0%), max 50.0%");
+    }
+
+    #[test]
+    fn test_learn_from_profile() {
+        let mut values = vec!["12"; 100];
+        values.extend(vec!["7"; 97]);
+        values.extend(["1234", "1.5", ""]);
+        let mut stats = ValueStats::default();
+        for raw in &values {
+            stats.observe(raw);
+        }
+        assert_eq!((stats.values, stats.nulls, stats.min_length, stats.max_length), (200, 1, Some(1), Some(4)));
+        let profile = Profile {
+            rows: values.len(),
+            ragged_rows: 0,
+            columns: vec![ColumnProfile {
+                name: "qty".to_string(),
+                masks: [("9", 198), ("9.9", 1), ("_", 1)].iter().map(|(m, c)| (m.to_string(), *c)).collect(),
+                stats,
+                inferred: Some(InferredType::Decimal { precision: 5, scale: 1 }),
+            }],
+        };
+
+        let learned = Expectations::learn(&profile, "LU", 0.01);
+        let qty = &learned.columns["qty"];
+        // Masks under 1% of values are left to the unexpected rate
+        assert_eq!(qty.allowed_masks, Some(vec!["9".to_string()]));
+        assert_eq!(qty.max_unexpected_rate, Some(0.01));
+        assert_eq!(qty.data_type, Some(DataType::Decimal));
+        assert_eq!(qty.max_null_rate, Some(0.005));
+        assert_eq!(qty.length, Some(LengthRange { min: Some(1), max: Some(4) }));
+
+        let yaml = serde_yaml::to_string(&learned).unwrap();
+        let reloaded: Expectations = serde_yaml::from_str(&yaml).unwrap();
+        assert!(reloaded.validate(&profile, None).passed());
+    }
+
+    #[test]
+    fn test_type_check_uses_the_inferred_type() {
+        assert_eq!(DataType::of_inferred(&InferredType::Integer { digits: 3 }), DataType::Integer);
+        assert_eq!(DataType::of_inferred(&InferredType::Datetime { format: None }), DataType::Datetime);
+
+        // Integers fit a decimal column, identifiers don't fit an integer one
+        let expectations: Expectations =
+            serde_yaml::from_str("columns:\n  id: { type: decimal }\n  code: { type: integer }\n").unwrap();
+        let mut id = column("id", &[("9", 4)]);
+        id.inferred = Some(InferredType::Integer { digits: 2 });
+        let mut code = column("code", &[("9", 4)]);
+        code.inferred = Some(InferredType::Identifier);
+        let profile = Profile { rows: 4, ragged_rows: 0, columns: vec![id, code] };
+        let validation = expectations.validate(&profile, None);
+        assert_eq!(failed(&validation, "type"), vec![Some("code".to_string())]);
+        assert_eq!(validation.violations[0].message, "expected integer, values look like string");
+    }
+}
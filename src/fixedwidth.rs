@@ -0,0 +1,454 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+lazy_static! {
+    static ref PIC_RE: Regex = Regex::new(r"\bPIC(?:TURE)?\s+(?:IS\s+)?(\S+)").unwrap();
+    static ref OCCURS_RE: Regex = Regex::new(r"\bOCCURS\s+(\d+)").unwrap();
+    static ref BINARY_RE: Regex =
+        Regex::new(r"\b(COMP(?:UTATIONAL)?(?:-[1-5])?|BINARY|PACKED-DECIMAL|POINTER|INDEX)\b").unwrap();
+    static ref REPEAT_RE: Regex = Regex::new(r"(.)\((\d+)\)").unwrap();
+    static ref LITERAL_RE: Regex = Regex::new(r#"'[^']*'|"[^"]*""#).unwrap();
+}
+
+/// A single field in a fixed-width record layout.
+/// `start` is a 0-based character offset into the record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedField {
+    pub name: String,
+    pub start: usize,
+    pub width: usize,
+}
+
+/// An item from a copybook, with the clauses after its name upper-cased and
+/// quoted literals emptied, so `VALUE 'COMP'` is not read as a usage
+#[derive(Debug)]
+struct CopybookItem {
+    level: u32,
+    name: String,
+    clauses: String,
+}
+
+/// Column layout used to slice fixed-width records into fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedWidthLayout {
+    pub fields: Vec<FixedField>,
+}
+
+impl FixedWidthLayout {
+    /// Load a layout from either a file path or an inline specification.
+    ///
+    /// Inline specifications are comma separated `name:start:width` triples
+    /// with 1-based start positions, e.g. `id:1:8,name:9:30,dob:39:8`.
+    /// Files may contain the same triples one per line (`name start width`,
+    /// whitespace, comma or colon separated), or a COBOL copybook.
+    pub fn load(spec_or_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if Path::new(spec_or_path).is_file() {
+            let text = std::fs::read_to_string(spec_or_path)?;
+            if Self::looks_like_copybook(&text) {
+                Ok(Self::parse_copybook(&text)?)
+            } else {
+                Ok(Self::parse_layout(&text)?)
+            }
+        } else {
+            Ok(Self::parse_layout(&spec_or_path.replace(',', "\n"))?)
+        }
+    }
+
+    fn looks_like_copybook(text: &str) -> bool {
+        text.lines().any(|line| {
+            let upper = line.to_uppercase();
+            upper.contains(" PIC ") || upper.contains(" PICTURE ")
+        })
+    }
+
+    /// Parse `name start width` lines (1-based start). Blank lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse_layout(text: &str) -> Result<Self, String> {
+        let mut fields = Vec::new();
+
+        for line in text.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line
+                .split(|c: char| c == ':' || c == ',' || c.is_whitespace())
+                .filter(|p| !p.is_empty())
+                .collect();
+            if parts.len() != 3 {
+                return Err(format!(
+                    "Invalid layout entry '{}': expected name, start and width",
+                    line
+                ));
+            }
+            let start: usize = parts[1]
+                .parse()
+                .map_err(|_| format!("Invalid start position in '{}'", line))?;
+            let width: usize = parts[2]
+                .parse()
+                .map_err(|_| format!("Invalid width in '{}'", line))?;
+            if start == 0 || width == 0 {
+                return Err(format!(
+                    "Invalid layout entry '{}': start is 1-based and width must be positive",
+                    line
+                ));
+            }
+            fields.push(FixedField {
+                name: parts[0].to_string(),
+                start: start - 1,
+                width,
+            });
+        }
+
+        Self::new(fields)
+    }
+
+    /// Parse a COBOL copybook. Elementary items with a PIC clause are laid out
+    /// sequentially; FILLER items take up space but are not reported, OCCURS
+    /// repeats an item, or a group with all its children, as `NAME[1]`,
+    /// `NAME[2]`, ... and items that REDEFINES another (with their children)
+    /// are skipped. Names used more than once are qualified with their group,
+    /// e.g. `HOME-ADDR.CITY`.
+    ///
+    /// Records are read as text, so binary and packed usages (COMP, COMP-1 to
+    /// COMP-5, BINARY, PACKED-DECIMAL, POINTER, INDEX) are rejected rather than
+    /// sliced at positions that would not match the bytes on disk.
+    pub fn parse_copybook(text: &str) -> Result<Self, String> {
+        let item_re = Regex::new(
+            r"(?i)^\s*(\d{1,2})\s+([A-Z0-9][A-Z0-9-]*)\b(.*)$",
+        )
+        .unwrap();
+
+        // Copybook lines often carry sequence numbers in columns 1-6 and
+        // continue statements over several lines, so join everything into
+        // period-terminated statements first. Columns 1-6 are only a sequence
+        // area when they hold digits or nothing, so `  05  ID PIC 9(3).` in a
+        // free-format copybook keeps its level number.
+        let mut statements = Vec::new();
+        let mut current = String::new();
+        for raw in text.lines() {
+            let line = match (raw.get(..6), raw.get(6..)) {
+                (Some(sequence), Some(rest))
+                    if sequence.chars().all(|c| c.is_ascii_digit()) || sequence.trim().is_empty() =>
+                {
+                    rest
+                }
+                _ => raw,
+            };
+            if line.trim_start().starts_with('*') {
+                continue;
+            }
+            current.push(' ');
+            current.push_str(line.trim());
+            if line.trim_end().ends_with('.') {
+                statements.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.trim().is_empty() {
+            statements.push(current);
+        }
+
+        let mut items = Vec::new();
+        let mut skip_below_level: Option<u32> = None;
+
+        for statement in statements.iter() {
+            let statement = statement.trim().trim_end_matches('.');
+            let caps = match item_re.captures(statement) {
+                Some(caps) => caps,
+                None => continue,
+            };
+            let level: u32 = caps[1].parse().unwrap();
+
+            // 66/77/88 items never occupy space in the record
+            if level == 66 || level == 77 || level == 88 {
+                continue;
+            }
+            if let Some(skip_level) = skip_below_level {
+                if level > skip_level {
+                    continue;
+                }
+                skip_below_level = None;
+            }
+            let clauses = LITERAL_RE.replace_all(&caps[3], "''").to_uppercase();
+            if clauses.contains("REDEFINES") {
+                skip_below_level = Some(level);
+                continue;
+            }
+            items.push(CopybookItem {
+                level,
+                name: caps[2].to_uppercase(),
+                clauses,
+            });
+        }
+
+        let mut placed = Vec::new();
+        let mut offset = 0;
+        Self::place_items(&items, "", "", &mut offset, &mut placed)?;
+
+        // Qualify names that occur more than once with their group
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (_, field) in &placed {
+            *counts.entry(field.name.clone()).or_insert(0) += 1;
+        }
+        let fields = placed
+            .into_iter()
+            .map(|(group, mut field)| {
+                if counts[&field.name] > 1 && !group.is_empty() {
+                    field.name = format!("{}.{}", group, field.name);
+                }
+                field
+            })
+            .collect();
+
+        Self::new(fields)
+    }
+
+    /// Lay out sibling items and their children from `offset`, collecting
+    /// each reported field with the name of the group it belongs to.
+    /// `subscripts` holds the OCCURS indices of the enclosing groups.
+    fn place_items(
+        items: &[CopybookItem],
+        group: &str,
+        subscripts: &str,
+        offset: &mut usize,
+        placed: &mut Vec<(String, FixedField)>,
+    ) -> Result<(), String> {
+        let mut idx = 0;
+        while idx < items.len() {
+            let item = &items[idx];
+            let end = items[idx + 1..]
+                .iter()
+                .position(|child| child.level <= item.level)
+                .map_or(items.len(), |pos| idx + 1 + pos);
+            let children = &items[idx + 1..end];
+            idx = end;
+
+            if let Some(usage) = BINARY_RE.captures(&item.clauses) {
+                return Err(format!(
+                    "Item '{}' uses {}: binary and packed fields cannot be sliced from text records, only DISPLAY usage is supported",
+                    item.name, &usage[1]
+                ));
+            }
+            let occurs: usize = OCCURS_RE
+                .captures(&item.clauses)
+                .map(|c| c[1].parse().unwrap())
+                .unwrap_or(1);
+            let pic = PIC_RE.captures(&item.clauses).map(|pic| pic[1].to_string());
+            if pic.is_none() && children.is_empty() {
+                return Err(format!("Item '{}' has neither a PIC clause nor subordinate items", item.name));
+            }
+
+            for occurrence in 1..=occurs {
+                let subscripts = if occurs > 1 {
+                    format!("{}[{}]", subscripts, occurrence)
+                } else {
+                    subscripts.to_string()
+                };
+                match &pic {
+                    Some(pic) => {
+                        let width = Self::picture_width(pic, &item.clauses)?;
+                        if item.name != "FILLER" {
+                            let field = FixedField {
+                                name: format!("{}{}", item.name, subscripts),
+                                start: *offset,
+                                width,
+                            };
+                            placed.push((group.to_string(), field));
+                        }
+                        *offset += width;
+                    }
+                    None => Self::place_items(children, &item.name, &subscripts, offset, placed)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Width in characters of a DISPLAY PIC clause
+    fn picture_width(pic: &str, clauses: &str) -> Result<usize, String> {
+        let expanded = REPEAT_RE.replace_all(pic, |caps: &regex::Captures| {
+            caps[1].repeat(caps[2].parse::<usize>().unwrap_or(1))
+        });
+
+        let mut width = 0;
+        for c in expanded.chars() {
+            match c.to_ascii_uppercase() {
+                // Sign and implied decimal point take no space unless SIGN SEPARATE
+                'S' => {
+                    if clauses.contains("SEPARATE") {
+                        width += 1;
+                    }
+                }
+                'V' | 'P' => {}
+                '9' | 'X' | 'A' | 'Z' | '*' | '+' | '-' | '.' | ',' | '$' | 'B' | '0' | '/' => width += 1,
+                other => return Err(format!("Unsupported PIC symbol '{}' in '{}'", other, pic)),
+            }
+        }
+        Ok(width)
+    }
+
+    fn new(fields: Vec<FixedField>) -> Result<Self, String> {
+        if fields.is_empty() {
+            return Err("Fixed-width layout defines no fields".to_string());
+        }
+        Ok(FixedWidthLayout { fields })
+    }
+
+    /// Field names, with `_2`, `_3`, ... added to repeats so every column
+    /// keeps its own entry in the report
+    pub fn column_names(&self) -> Vec<String> {
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        self.fields
+            .iter()
+            .map(|f| {
+                let count = seen.entry(f.name.as_str()).or_insert(0);
+                *count += 1;
+                if *count == 1 {
+                    f.name.clone()
+                } else {
+                    format!("{}_{}", f.name, count)
+                }
+            })
+            .collect()
+    }
+
+    /// Expected record width in characters
+    pub fn record_width(&self) -> usize {
+        self.fields.iter().map(|f| f.start + f.width).max().unwrap_or(0)
+    }
+
+    /// Slice a record into fields by character position, trimming trailing
+    /// padding. Short records yield fewer fields, and any text beyond the
+    /// layout's record width is returned as one extra field, so both show up
+    /// in the ragged-row checks of the tabular report.
+    pub fn slice(&self, line: &str) -> Vec<String> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut values = Vec::with_capacity(self.fields.len());
+
+        for field in &self.fields {
+            if field.start >= chars.len() {
+                break;
+            }
+            let end = (field.start + field.width).min(chars.len());
+            let value: String = chars[field.start..end].iter().collect();
+            values.push(value.trim_end().to_string());
+        }
+
+        let record_width = self.record_width();
+        if chars.len() > record_width {
+            values.push(chars[record_width..].iter().collect());
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_layout_and_slice() {
+        let layout = FixedWidthLayout::load("id:1:3,name:4:6,amt:10:4").unwrap();
+        assert_eq!(layout.record_width(), 13);
+        assert_eq!(layout.slice("001Smith 0042"), vec!["001", "Smith", "0042"]);
+        // short record: missing fields are dropped
+        assert_eq!(layout.slice("002Jo"), vec!["002", "Jo"]);
+        // long record: overflow becomes an extra field
+        assert_eq!(layout.slice("003Brown 0001XX"), vec!["003", "Brown", "0001", "XX"]);
+    }
+
+    #[test]
+    fn test_copybook_layout() {
+        let copybook = "\
+       01  CUSTOMER-REC.
+           05  CUST-ID        PIC 9(6).
+           05  CUST-NAME      PIC X(20).
+           05  FILLER         PIC X(2).
+           05  BALANCE        PIC S9(7)V99.
+           05  ALT-BALANCE REDEFINES BALANCE PIC X(9).
+           05  PHONE          PIC X(10) OCCURS 2.
+";
+        let layout = FixedWidthLayout::parse_copybook(copybook).unwrap();
+        let names = layout.column_names();
+        assert_eq!(names, vec!["CUST-ID", "CUST-NAME", "BALANCE", "PHONE[1]", "PHONE[2]"]);
+        assert_eq!(layout.fields[2].start, 28);
+        assert_eq!(layout.fields[2].width, 9);
+        assert_eq!(layout.fields[3].start, 37);
+        assert_eq!(layout.record_width(), 57);
+    }
+
+    #[test]
+    fn test_copybook_sequence_numbers_are_stripped() {
+        let copybook = "\
+000100 01  REC.
+000200*    A COMMENT
+000300     05  ID             PIC 9(3).
+";
+        let layout = FixedWidthLayout::parse_copybook(copybook).unwrap();
+        assert_eq!(layout.column_names(), vec!["ID"]);
+    }
+
+    #[test]
+    fn test_free_format_indented_copybook() {
+        let layout = FixedWidthLayout::parse_copybook("01 REC.\n  05  ID PIC 9(3).\n  05  NAME PIC X(5).").unwrap();
+        assert_eq!(layout.column_names(), vec!["ID", "NAME"]);
+        assert_eq!(layout.slice("001Smith"), vec!["001", "Smith"]);
+    }
+
+    #[test]
+    fn test_copybook_group_occurs_repeats_children() {
+        let copybook = "\
+01 REC.
+  05 GRP OCCURS 2.
+    10 C PIC X(2).
+    10 D PIC 9.
+  05 E PIC X(3).
+";
+        let layout = FixedWidthLayout::parse_copybook(copybook).unwrap();
+        assert_eq!(layout.column_names(), vec!["C[1]", "D[1]", "C[2]", "D[2]", "E"]);
+        assert_eq!(layout.slice("aa1bb2eee"), vec!["aa", "1", "bb", "2", "eee"]);
+    }
+
+    #[test]
+    fn test_copybook_rejects_binary_usage() {
+        for usage in ["COMP-2", "PIC S9(5) COMP-3", "PIC 9(4) COMP", "PIC 9(4) BINARY", "PIC 9(4) USAGE IS COMPUTATIONAL-5"] {
+            let copybook = format!("01 REC.\n  05 A PIC X(2).\n  05 R {}.\n  05 B PIC X(3).", usage);
+            let err = FixedWidthLayout::parse_copybook(&copybook).unwrap_err();
+            assert!(err.contains("Item 'R' uses"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_copybook_usage_words_in_literals_are_text() {
+        let copybook = "01 REC.\n  05 KIND PIC X(5) VALUE 'INDEX'.\n  05 MODE PIC X(4) VALUE \"COMP\".";
+        let layout = FixedWidthLayout::parse_copybook(copybook).unwrap();
+        assert_eq!(layout.fields[1].start, 5);
+        assert_eq!(layout.fields[1].width, 4);
+    }
+
+    #[test]
+    fn test_copybook_repeated_names_are_qualified() {
+        let copybook = "\
+01 REC.
+  05 HOME-ADDR.
+    10 CITY PIC X(3).
+  05 WORK-ADDR.
+    10 CITY PIC X(3).
+";
+        let layout = FixedWidthLayout::parse_copybook(copybook).unwrap();
+        assert_eq!(layout.column_names(), vec!["HOME-ADDR.CITY", "WORK-ADDR.CITY"]);
+    }
+
+    #[test]
+    fn test_repeated_layout_names_get_suffixes() {
+        let layout = FixedWidthLayout::load("id:1:3,code:4:2,code:6:2").unwrap();
+        assert_eq!(layout.column_names(), vec!["id", "code", "code_2"]);
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        assert!(FixedWidthLayout::parse_layout("id 0 3").is_err());
+        assert!(FixedWidthLayout::parse_layout("id 1").is_err());
+    }
+}
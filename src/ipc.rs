@@ -0,0 +1,143 @@
+#[cfg(feature = "ipc")]
+use crate::arrow_json::record_batch_to_json_lines;
+#[cfg(feature = "ipc")]
+use arrow::error::ArrowError;
+#[cfg(feature = "ipc")]
+use arrow::ipc::reader::{FileReader, StreamReader};
+#[cfg(feature = "ipc")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "ipc")]
+use std::fs::File;
+#[cfg(feature = "ipc")]
+use std::io::{BufReader, Cursor, Seek, SeekFrom};
+use std::io::Read;
+use std::path::Path;
+
+#[cfg(feature = "ipc")]
+/// Magic bytes at the start of an Arrow IPC file (Feather v2)
+const ARROW_FILE_MAGIC: &[u8; 6] = b"ARROW1";
+
+#[cfg(feature = "ipc")]
+/// Reader for Arrow IPC data: the random-access file format (also written
+/// as Feather v2) and the streaming format.
+pub struct ArrowIpcReader;
+
+#[cfg(feature = "ipc")]
+impl ArrowIpcReader {
+    /// Read an Arrow IPC file or stream from disk and return JSON lines
+    /// (one JSON object per row). The format is detected from the magic bytes.
+    pub fn read_as_json_lines<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut file = File::open(&path)?;
+        let mut magic = [0u8; 6];
+        let is_file_format = file.read_exact(&mut magic).is_ok() && &magic == ARROW_FILE_MAGIC;
+        file.seek(SeekFrom::Start(0))?;
+
+        if is_file_format {
+            Self::collect(FileReader::try_new(file, None)?)
+        } else {
+            Self::collect(StreamReader::try_new(BufReader::new(file), None)?)
+        }
+    }
+
+    /// Read Arrow IPC data from a non-seekable source such as stdin.
+    /// The file format needs random access, so the input is buffered in memory.
+    pub fn read_from_reader<R: Read>(
+        mut input: R,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+
+        if data.starts_with(ARROW_FILE_MAGIC) {
+            Self::collect(FileReader::try_new(Cursor::new(data), None)?)
+        } else {
+            Self::collect(StreamReader::try_new(Cursor::new(data), None)?)
+        }
+    }
+
+    fn collect<I>(batches: I) -> Result<Vec<String>, Box<dyn std::error::Error>>
+    where
+        I: Iterator<Item = Result<RecordBatch, ArrowError>>,
+    {
+        let mut json_lines = Vec::new();
+        for batch_result in batches {
+            let batch = batch_result?;
+            json_lines.extend(record_batch_to_json_lines(&batch)?);
+        }
+        Ok(json_lines)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dummy implementation when ipc feature is not enabled
+// ---------------------------------------------------------------------------
+
+#[cfg(not(feature = "ipc"))]
+pub struct ArrowIpcReader;
+
+#[cfg(not(feature = "ipc"))]
+impl ArrowIpcReader {
+    pub fn read_as_json_lines<P: AsRef<Path>>(
+        _path: P,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Err("Arrow IPC support not enabled. Rebuild with --features ipc".into())
+    }
+
+    pub fn read_from_reader<R: Read>(
+        _input: R,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Err("Arrow IPC support not enabled. Rebuild with --features ipc".into())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+#[cfg(feature = "ipc")]
+mod tests {
+    use super::*;
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::writer::{FileWriter, StreamWriter};
+    use std::sync::Arc;
+
+    fn sample_batch() -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("city", DataType::Utf8, true),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int64Array::from(vec![7, 8])),
+                Arc::new(StringArray::from(vec![Some("Leeds"), None])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_read_file_and_stream_formats() {
+        let batch = sample_batch();
+
+        let mut file_bytes = Vec::new();
+        {
+            let mut writer = FileWriter::try_new(&mut file_bytes, &batch.schema()).unwrap();
+            writer.write(&batch).unwrap();
+            writer.finish().unwrap();
+        }
+        let mut stream_bytes = Vec::new();
+        {
+            let mut writer = StreamWriter::try_new(&mut stream_bytes, &batch.schema()).unwrap();
+            writer.write(&batch).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let expected = vec![r#"{"city":"Leeds","id":7}"#, r#"{"city":null,"id":8}"#];
+        assert_eq!(ArrowIpcReader::read_from_reader(&file_bytes[..]).unwrap(), expected);
+        assert_eq!(ArrowIpcReader::read_from_reader(&stream_bytes[..]).unwrap(), expected);
+    }
+}
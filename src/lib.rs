@@ -1,7 +1,17 @@
 // Library module for WASM and other uses
 
+pub mod arrow_profile;
 pub mod cache;
+pub mod charprofile;
+pub mod cleanse;
+pub mod config;
+pub mod decompress;
+pub mod expectations;
+pub mod fixedwidth;
+pub mod mask;
 pub mod rules;
+pub mod structure;
+pub mod types;
 
 #[cfg(feature = "excel")]
 pub mod excel;
@@ -9,6 +19,13 @@ pub mod excel;
 #[cfg(feature = "parquet")]
 pub mod parquet;
 
+#[cfg(any(feature = "parquet", feature = "ipc"))]
+pub mod arrow_json;
+
+pub mod avro;
+pub mod ipc;
+pub mod xml;
+
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
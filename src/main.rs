@@ -5,16 +5,35 @@ use chrono::Local;
 use clap::{App, Arg};
 use rand::prelude::*;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{self, BufRead, Read};
-use unic::ucd::GeneralCategory as Category;
 use unicode_names2;
 use serde_json::json;
 use bytefreq::rules::enhancer::process_data;
-use bytefreq::excel::ExcelReader;
-use bytefreq::parquet::ParquetReader;
+use bytefreq::rules::registry::{set_active_rules, with_active_rules, RuleRegistry};
+use bytefreq::rules::scorecard::Scorecard;
+use bytefreq::rules::dates::{set_date_parser, with_date_parser, DateInference, DateOrder, DateParser};
+use bytefreq::rules::postcodes::{is_postcode_field, PostcodeProfile};
+use bytefreq::rules::treatment::{set_active_treatments, with_active_treatments, TreatmentSet};
+use bytefreq::rules::declarative::load_rules_file;
+use bytefreq::cleanse::{unique_name, value_text, Cleanser};
+use bytefreq::mask::mask_value;
+use bytefreq::charprofile::{suspicious_flags, ColumnCharProfile, ExpectedChars, SuspiciousSummary};
+use bytefreq::structure::FileStructure;
+use bytefreq::types::{arrow_schema, sql_schema, TypeProfile, TypeSuggestion};
+use bytefreq::expectations::{ColumnProfile, Expectations, Profile, RecordStats, Validation};
+use bytefreq::fixedwidth::FixedWidthLayout;
+use bytefreq::excel::{
+    column_letters, guess_header_row, CellType, ExcelCell, ExcelOptions, ExcelReader, ExcelRegion, SheetOverview,
+};
+use bytefreq::arrow_profile::ColumnPatterns;
+use bytefreq::parquet::{ParquetOptions, ParquetReader};
+use bytefreq::xml::XmlReader;
+use bytefreq::decompress::{open_path, open_reader};
+use bytefreq::avro::AvroReader;
+use bytefreq::ipc::ArrowIpcReader;
 use rayon::prelude::*;
-use csv::ReaderBuilder;
+use csv::{ReaderBuilder, WriterBuilder};
 
 use std::sync::{Arc, Mutex};
 use std::sync::RwLock;
@@ -31,88 +50,6 @@ lazy_static! {
 
 
 
-pub fn identity_mask(value: &str) -> String {
-    value.to_string()
-}
-
-// this is a highgrain Mask that works for unicode data!
-fn high_grain_unicode_mask(c: char) -> char {
-    match c {
-        '0'..='9' => '9',
-        'a'..='z' => 'a',
-        'A'..='Z' => 'A',
-        c if c.is_ascii_punctuation() && (c == '"' || c == '-' || c == '.' || c == ',') => c,
-        c if c.is_whitespace() => ' ',
-        _ => {
-            let cat = Category::of(c);
-
-            match cat {
-                Category::UppercaseLetter => 'A',
-                Category::LowercaseLetter => 'a',
-                Category::TitlecaseLetter => 'A',
-                Category::OtherLetter => 'a',
-                Category::ModifierLetter => 'a',
-                Category::DecimalNumber => '9',
-                Category::LetterNumber => '9',
-                Category::OtherNumber => '9',
-                Category::SpaceSeparator => ' ',
-                Category::LineSeparator => ' ',
-                Category::ParagraphSeparator => ' ',
-                _ => '_',
-            }
-        }
-    }
-}
-
-fn high_grain_mask(value: &str) -> String {
-    value
-        .chars()
-        .map(|c| match c {
-            'a'..='z' => 'a',
-            'A'..='Z' => 'A',
-            '0'..='9' => '9',
-            _ => c,
-        })
-        .collect()
-}
-
-fn low_grain_mask(value: &str) -> String {
-    let high_grain = high_grain_mask(value);
-    let mut output = String::new();
-    let mut last_char = None;
-
-    for c in high_grain.chars() {
-        if last_char != Some(c) {
-            output.push(c);
-            last_char = Some(c);
-        }
-    }
-    if output.is_empty() {
-        "_".to_string()
-    } else {
-        output
-    }
-}
-
-fn mask_value(value: &str, grain: &str, field_name: &str) -> String {
-    if field_name.contains(".Rules.") {
-        identity_mask(value)
-    } else {
-        match grain {
-            "H" => high_grain_mask(value),
-            "L" => low_grain_mask(value),
-            "HU" => value.chars().map(|c| high_grain_unicode_mask(c)).collect(),
-            "LU" => low_grain_mask(
-                &value
-                    .chars()
-                    .map(|c| high_grain_unicode_mask(c))
-                    .collect::<String>(),
-            ),
-            _u => value.chars().map(|c| high_grain_unicode_mask(c)).collect(),
-        }
-    }
-}
-
 fn process_json_value(
     value: &Value,
     frequency_maps: &mut Vec<HashMap<String, usize>>,
@@ -141,8 +78,8 @@ fn process_json_value(
                         full_key,
                         column_names,
                         remove_array_numbers,
-                        pathdepth + 1,
-                        current_depth,
+                        pathdepth,
+                        current_depth + 1,
                     );
                 }
             }
@@ -162,7 +99,7 @@ fn process_json_value(
                     full_key,
                     column_names,
                     remove_array_numbers,
-                    pathdepth + 1,
+                    pathdepth,
                     current_depth,
                 );
             }
@@ -208,12 +145,15 @@ fn process_tabular_line_as_json(processed_fields: &Vec<(String, String)>) -> ser
 
             let assertions = process_data(&column_name, &data);
 
-            let enhanced_value = json!({
+            let mut enhanced_value = json!({
                 "raw": value,
                 "HU": hu_masked_value,
                 "LU": lu_masked_value,
                 "Rules": assertions
             });
+            if let Some(treatment) = treat_value(column_name, value, &hu_masked_value, &lu_masked_value) {
+                enhanced_value["Treatment"] = treatment;
+            }
 
             (column_name.clone(), enhanced_value)
         })
@@ -317,6 +257,45 @@ fn process_json_line(
     }
 }
 
+/// Collect the leaf values of a JSON document as (path, value) pairs, using the
+/// same dotted path naming as `process_json_value`. String values are returned
+/// without their JSON quotes so their characters can be inspected directly.
+fn collect_json_fields(
+    value: &Value,
+    prefix: String,
+    remove_array_numbers: bool,
+    pathdepth: usize,
+    current_depth: usize,
+    fields: &mut Vec<(String, String)>,
+) {
+    match value {
+        Value::Object(map) => {
+            if current_depth < pathdepth {
+                for (key, value) in map.iter() {
+                    let full_key = if prefix.is_empty() {
+                        key.to_string()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    collect_json_fields(value, full_key, remove_array_numbers, pathdepth, current_depth + 1, fields);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for (idx, value) in values.iter().enumerate() {
+                let full_key = if remove_array_numbers {
+                    format!("{}[]", prefix)
+                } else {
+                    format!("{}[{}]", prefix, idx)
+                };
+                collect_json_fields(value, full_key, remove_array_numbers, pathdepth, current_depth, fields);
+            }
+        }
+        Value::String(s) => fields.push((prefix, s.clone())),
+        other => fields.push((prefix, other.to_string())),
+    }
+}
+
 fn init_control_character_descriptions() -> HashMap<char, &'static str> {
     let mut ref_map = HashMap::new();
     ref_map.insert('\u{0000}', "NUL - Null char");
@@ -432,6 +411,7 @@ fn init_control_character_descriptions() -> HashMap<char, &'static str> {
 struct LineReader<R: Read> {
     inner: R,
     buf: Vec<u8>,
+    structure: FileStructure,
 }
 
 impl<R: BufRead> LineReader<R> {
@@ -439,12 +419,14 @@ impl<R: BufRead> LineReader<R> {
         Self {
             inner,
             buf: Vec::new(),
+            structure: FileStructure::new(),
         }
     }
 
     fn read_line_self(&mut self) -> io::Result<Option<String>> {
         let mut line = Vec::new();
         let bytes_read = self.inner.read_until(b'\n', &mut line)?;
+        self.structure.observe_line(&line);
 
         if bytes_read == 0 {
             if !self.buf.is_empty() {
@@ -501,8 +483,9 @@ fn character_profiling() -> Result<(), std::io::Error> {
     let ascii_control_characters = init_control_character_descriptions();
     let stdin = io::stdin();
     let mut frequency_map: HashMap<char, usize> = HashMap::new();
+    let mut suspicious_summary = SuspiciousSummary::default();
 
-    let file_reader: Box<dyn BufRead> = Box::new(stdin.lock());
+    let file_reader: Box<dyn BufRead> = open_reader(stdin.lock())?;
 
     let mut reader = LineReader::new(file_reader);
 
@@ -512,22 +495,24 @@ fn character_profiling() -> Result<(), std::io::Error> {
             let count = frequency_map.entry(c).or_insert(0);
             *count += 1;
         }
+        suspicious_summary.add_line(&line);
         line.clear();
     }
 
     println!(
-        "{:<6}\t{:<8}\t{:<8}\t{}\t{}",
+        "{:<6}\t{:<8}\t{:<8}\tdescription\tname\tflags",
         //"{:<8}\t{:<8}\t{}\t{}",
-        "hex", "char", "count", "description", "name"
+        "hex", "char", "count"
         //"char", "count", "description", "name"
     );
     //println!("{:-<8}\t{:-<8}\t{:-<15}\t{:-<15}", "", "", "", "");
-    println!("{:-<6}\t{:-<8}\t{:-<8}\t{:-<15}\t{:-<15}", "", "", "", "", "");
+    println!("{:-<6}\t{:-<8}\t{:-<8}\t{:-<15}\t{:-<15}\t{:-<15}", "", "", "", "", "", "");
 
     let mut sorted_chars: Vec<(char, usize)> = frequency_map.into_iter().collect();
     sorted_chars.sort_unstable_by_key(|&(c, _)| c as u32);
 
-    for (c, count) in sorted_chars {
+    for &(c, count) in &sorted_chars {
+        suspicious_summary.add_char(c, count);
         let character_name = unicode_names2::name(c).map_or_else(
             || {
                 ascii_control_characters
@@ -538,68 +523,241 @@ fn character_profiling() -> Result<(), std::io::Error> {
         );
         let hex_repr = format!("{:X}", c as u32);  // Convert char to its hexadecimal representation
         println!(
-            "{:-<6}\t{:<10}\t{:<8}\t{:<8}\t{}",
+            "{:-<6}\t{:<10}\t{:<8}\t{:<8}\t{}\t{}",
             //"{:<8}\t{:<8}\t{}\t{}",
             hex_repr,
             c.escape_unicode(),
             count,
             c.escape_debug(),
-            character_name
+            character_name,
+            suspicious_flags(c)
         );
     }
+
+    println!();
+    println!("{}", reader.structure);
+
+    println!();
+    println!("Suspicious Characters:");
+    println!("{:<48}\t{:<8}\t{:<8}", "category", "distinct", "count");
+    println!("{:-<48}\t{:-<8}\t{:-<8}", "", "", "");
+    if suspicious_summary.categories.is_empty() {
+        println!("(none)");
+    }
+    for (category, (distinct, occurrences)) in &suspicious_summary.categories {
+        println!("{:<48}\t{:<8}\t{:<8}", category.description(), distinct, occurrences);
+    }
+    println!(
+        "Combining mark sequences: {} ({} could be precomposed by NFC)",
+        suspicious_summary.combining_sequences, suspicious_summary.composable_sequences
+    );
+    Ok(())
+}
+
+/// Character profiling split by column: parses stdin the same way as the DQ report
+/// (CSV with a header row, or JSON lines) and reports, per column, each character
+/// outside the expected set with its count, example rows and an example value.
+fn column_character_profiling(
+    format: &str,
+    delimiter: u8,
+    header_row: usize,
+    pathdepth: usize,
+    remove_array_numbers: bool,
+    expected: ExpectedChars,
+    maxlen: usize,
+) -> Result<(), std::io::Error> {
+    let ascii_control_characters = init_control_character_descriptions();
+    let mut profile = ColumnCharProfile::new(expected);
+    let mut record_count = 0;
+
+    let stdin = io::stdin();
+    let mut headers: Vec<String> = Vec::new();
+
+    for (line_idx, line) in open_reader(stdin.lock())?.lines().enumerate() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+
+        if format == "json" {
+            if let Ok(json_value) = serde_json::from_str::<Value>(&line) {
+                record_count += 1;
+                let mut fields = Vec::new();
+                collect_json_fields(&json_value, String::new(), remove_array_numbers, pathdepth, 0, &mut fields);
+                for (name, value) in fields {
+                    profile.add_value(&name, &value, record_count);
+                }
+            }
+        } else if line_idx == header_row {
+            headers = parse_csv_line(&line, delimiter)
+                .iter()
+                .map(|s| s.trim().replace(" ", "_"))
+                .collect();
+            for name in &headers {
+                profile.add_column(name);
+            }
+        } else if line_idx > header_row {
+            record_count += 1;
+            for (i, field) in parse_csv_line(&line, delimiter).iter().enumerate() {
+                let column_name = match headers.get(i) {
+                    Some(name) => name.clone(),
+                    None => format!("RaggedErr{}", i + 1 - headers.len()),
+                };
+                profile.add_value(&column_name, field, record_count);
+            }
+        }
+    }
+
+    let now_string = Local::now().format("%Y%m%d %H:%M:%S").to_string();
+    println!("Column Character Profiling Report: {}", now_string);
+    println!("Examined rows: {}", record_count);
+    println!("Expected characters: {}", profile.expected().name());
+    println!();
+    println!(
+        "{:<32}\t{:<6}\t{:<10}\t{:<8}\t{:<16}\t{:<32}\tname",
+        "column", "hex", "char", "count", "rows", "example"
+    );
+    println!("{:-<32}\t{:-<6}\t{:-<10}\t{:-<8}\t{:-<16}\t{:-<32}\t{:-<15}", "", "", "", "", "", "", "");
+
+    let columns = profile.columns();
+    let mut flagged_columns = 0;
+    for (idx, name, chars) in &columns {
+        if !chars.is_empty() {
+            flagged_columns += 1;
+        }
+        for (c, stats) in chars {
+            let character_name = unicode_names2::name(*c).map_or_else(
+                || {
+                    ascii_control_characters
+                        .get(c)
+                        .map_or("UNKNOWN".to_string(), |desc| desc.to_string())
+                },
+                |name| name.to_string(),
+            );
+            let rows = stats
+                .example_rows
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            println!(
+                "col_{:05}_{}\t{:<6X}\t{:<10}\t{:<8}\t{:<16}\t{:<32}\t{}",
+                idx,
+                name,
+                *c as u32,
+                c.escape_unicode(),
+                stats.count,
+                rows,
+                truncate_string(&stats.example_value.escape_debug().to_string(), maxlen),
+                character_name
+            );
+        }
+    }
+
+    println!();
+    println!(
+        "Columns with unexpected characters: {} of {}",
+        flagged_columns,
+        columns.len()
+    );
     Ok(())
 }
 
+/// The treatment applied to a value in enhanced output, when --treatments is set
+/// and one matches
+fn treat_value(field_name: &str, raw: &str, hu: &str, lu: &str) -> Option<serde_json::Value> {
+    with_active_treatments(|treatments| {
+        treatments?
+            .apply(field_name, raw, hu, lu)
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+    })
+}
+
+/// Values touched per treatment and the masks no treatment covered, on stderr
+/// so they stay out of the enhanced output
+fn print_treatment_summary(treatments: &TreatmentSet) {
+    eprintln!();
+    eprintln!("Treatments:");
+    eprintln!("{:<32}\t{:<24}\t{:<16}\t{:<8}\t{:<8}", "treatment", "column", "mask", "values", "failed");
+    eprintln!("{:-<32}\t{:-<24}\t{:-<16}\t{:-<8}\t{:-<8}", "", "", "", "", "");
+    for (name, count) in treatments.counts() {
+        eprintln!(
+            "{:<32}\t{:<24}\t{:<16}\t{:<8}\t{:<8}",
+            name,
+            count.column.as_deref().unwrap_or("*"),
+            count.mask,
+            count.values,
+            count.failed
+        );
+    }
+    let untreated = treatments.untreated();
+    let total: usize = untreated.iter().map(|(_, _, count)| count).sum();
+    eprintln!();
+    eprintln!("Untreated: {} values", total);
+    for (column, mask, count) in untreated {
+        eprintln!("{:<32}\t{:<24}\t{:<16}\t{:<8}", "-", column, mask, count);
+    }
+}
+
 // updated for parallel processing with rayon:
 fn process_json_line_as_json(json_line: &str, grain: &str) -> serde_json::Value {
     let mut json_data: serde_json::Value = serde_json::from_str(json_line).unwrap();
 
-    fn process_json_value(json_value: &mut serde_json::Value, grain: &str) {
+    // `path` is the dotted path of the value, with array indices written as
+    // `[]`, which is how rules and treatments name JSON columns
+    fn process_json_value(json_value: &mut serde_json::Value, grain: &str, path: &str) {
         match json_value {
             serde_json::Value::Object(ref mut map) => {
                 let mut new_entries: Vec<(String, serde_json::Value)> = Vec::new();
                 for (key, value) in map.iter_mut() {
-                    process_json_value(value, grain);
+                    let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    process_json_value(value, grain, &child_path);
 
                     // Handle String values
                     if let serde_json::Value::String(s) = value {
-                        let hu_masked_value = mask_value(s, "HU", key);
-                        let lu_masked_value = mask_value(s, "LU", key);
+                        let hu_masked_value = mask_value(s, "HU", &child_path);
+                        let lu_masked_value = mask_value(s, "LU", &child_path);
 
                         let temp_data = json!({
                             "raw": s,
                             "HU": hu_masked_value,
                             "LU": lu_masked_value
                         });
-                        let assertions = process_data(key, &temp_data).unwrap_or(serde_json::Value::Null);
+                        let assertions = process_data(&child_path, &temp_data).unwrap_or(serde_json::Value::Null);
 
-                        let enhanced_value = json!({
+                        let mut enhanced_value = json!({
                             "raw": s,
                             "HU": hu_masked_value,
                             "LU": lu_masked_value,
                             "Rules": assertions
                         });
+                        if let Some(treatment) = treat_value(&child_path, s, &hu_masked_value, &lu_masked_value) {
+                            enhanced_value["Treatment"] = treatment;
+                        }
                         new_entries.push((key.clone(), enhanced_value));
                     }
                     // Handle Number values (integers and floats)
                     else if let serde_json::Value::Number(n) = value {
                         let s = n.to_string();
-                        let hu_masked_value = mask_value(&s, "HU", key);
-                        let lu_masked_value = mask_value(&s, "LU", key);
+                        let hu_masked_value = mask_value(&s, "HU", &child_path);
+                        let lu_masked_value = mask_value(&s, "LU", &child_path);
 
                         let temp_data = json!({
                             "raw": s,
                             "HU": hu_masked_value,
                             "LU": lu_masked_value
                         });
-                        let assertions = process_data(key, &temp_data).unwrap_or(serde_json::Value::Null);
+                        let assertions = process_data(&child_path, &temp_data).unwrap_or(serde_json::Value::Null);
 
-                        let enhanced_value = json!({
+                        let mut enhanced_value = json!({
                             "raw": n, // Keep original number type
                             "HU": hu_masked_value,
                             "LU": lu_masked_value,
                             "Rules": assertions
                         });
+                        if let Some(treatment) = treat_value(&child_path, &s, &hu_masked_value, &lu_masked_value) {
+                            enhanced_value["Treatment"] = treatment;
+                        }
                         new_entries.push((key.clone(), enhanced_value));
                     }
                 }
@@ -608,13 +766,14 @@ fn process_json_line_as_json(json_line: &str, grain: &str) -> serde_json::Value
                 }
             }
             serde_json::Value::Array(ref mut values) => {
-                values.par_iter_mut().for_each(|value| process_json_value(value, grain));
+                let child_path = format!("{}[]", path);
+                values.par_iter_mut().for_each(|value| process_json_value(value, grain, &child_path));
             }
             _ => {}
         }
     }
 
-    process_json_value(&mut json_data, grain);
+    process_json_value(&mut json_data, grain, "");
     json_data
 }
 
@@ -634,44 +793,872 @@ fn truncate_string(input: &str, max_length: usize) -> String {
     result
 }
 
-/// Parse a CSV line using proper CSV quoting rules
-fn parse_csv_line(line: &str, delimiter: u8) -> Vec<String> {
-    let mut reader = ReaderBuilder::new()
-        .delimiter(delimiter)
-        .has_headers(false)
-        .from_reader(line.as_bytes());
+/// Read all lines like `BufRead::lines` (dropping `\n` / `\r\n` terminators and
+/// skipping lines that are not valid UTF-8), while recording the raw file structure.
+fn read_lines_with_structure<R: BufRead>(
+    mut reader: R,
+    structure: &mut FileStructure,
+) -> io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        structure.observe_line(&buf);
 
-    if let Some(result) = reader.records().next() {
-        match result {
-            Ok(record) => record.iter().map(|s| s.to_string()).collect(),
-            Err(_) => {
-                // Fallback to naive splitting if CSV parsing fails
-                line.split(delimiter as char).map(|s| s.to_string()).collect()
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+        }
+        if let Ok(line) = String::from_utf8(buf.clone()) {
+            lines.push(line);
+        }
+    }
+    Ok(lines)
+}
+
+/// Run the active rules over a value and add the results to the scorecard
+fn score_value(scorecard: &Mutex<Scorecard>, field_name: &str, raw: &str) {
+    let hu = mask_value(raw, "HU", field_name);
+    let lu = mask_value(raw, "LU", field_name);
+    with_active_rules(|rules| {
+        let results = rules.evaluate_each(field_name, raw, &lu, &hu);
+        scorecard.lock().unwrap().observe(field_name, raw, &results);
+    });
+}
+
+/// Try a value against the date formats, skipping values without digits
+fn observe_date(date_inference: &Mutex<HashMap<String, DateInference>>, field_name: &str, raw: &str) {
+    if !raw.chars().any(|c| c.is_ascii_digit()) || raw.len() > 40 {
+        return;
+    }
+    with_date_parser(|parser| {
+        date_inference
+            .lock()
+            .unwrap()
+            .entry(field_name.to_string())
+            .or_default()
+            .observe(parser, raw)
+    });
+}
+
+/// Infer each column's date format from all of its values and hand the
+/// formats to the date parser, so `std_date` reads an ambiguous value such as
+/// 03/04/2015 the same way as the rest of its column
+fn set_column_date_formats(fields: impl Iterator<Item = (String, String)>) {
+    let date_inference = Mutex::new(HashMap::new());
+    for (name, raw) in fields {
+        observe_date(&date_inference, &name, &raw);
+    }
+    let mut parser = with_date_parser(|parser| parser.clone());
+    for (name, inference) in date_inference.into_inner().unwrap() {
+        if let Some(format) = inference.column_format(&parser) {
+            parser.set_column_format(&name, &format);
+        }
+    }
+    set_date_parser(parser);
+}
+
+/// Scalar values of a JSON record under the names the rules see them by in
+/// enhanced output and when cleansing: the dotted path, with `[]` for arrays
+fn json_rule_fields(value: &Value, path: &str, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                json_rule_fields(child, &child_path, fields);
+            }
+        }
+        Value::Array(values) => {
+            let child_path = format!("{}[]", path);
+            for child in values {
+                json_rule_fields(child, &child_path, fields);
+            }
+        }
+        Value::String(s) => fields.push((path.to_string(), s.clone())),
+        Value::Number(n) => fields.push((path.to_string(), n.to_string())),
+        _ => {}
+    }
+}
+
+/// Match values of post* and zip* columns against the postal code formats
+fn observe_postcode(postcode_profiles: &Mutex<HashMap<String, PostcodeProfile>>, field_name: &str, raw: &str) {
+    if !is_postcode_field(field_name) {
+        return;
+    }
+    postcode_profiles
+        .lock()
+        .unwrap()
+        .entry(field_name.to_string())
+        .or_default()
+        .observe(raw)
+}
+
+/// Record the shape of a value for type inference
+fn observe_type(type_profiles: &Mutex<HashMap<String, TypeProfile>>, field_name: &str, raw: &str) {
+    let lu = mask_value(raw.trim(), "LU", field_name);
+    with_date_parser(|parser| {
+        type_profiles
+            .lock()
+            .unwrap()
+            .entry(field_name.to_string())
+            .or_default()
+            .observe(parser, raw, &lu)
+    });
+}
+
+/// Recommended types in column order
+fn type_suggestions(type_profiles: &HashMap<String, TypeProfile>, columns: &[(String, usize)]) -> Vec<TypeSuggestion> {
+    with_date_parser(|parser| {
+        columns
+            .iter()
+            .map(|(name, _)| type_profiles.get(name).cloned().unwrap_or_default().suggest(name, parser))
+            .collect()
+    })
+}
+
+/// Recommended type per column with the share of values that fit it
+fn print_types(suggestions: &[TypeSuggestion], columns: &[(String, usize)], maxlen: usize) {
+    println!();
+    println!("Types:");
+    println!(
+        "{:<32}\t{:<24}\t{:<8}\t{:<8}\t{:<8}\t{:<32}",
+        "column", "type", "fit", "nulls", "maxlen", "counter-examples"
+    );
+    println!("{:-<32}\t{:-<24}\t{:-<8}\t{:-<8}\t{:-<8}\t{:-<32}", "", "", "", "", "", "");
+    for (suggestion, (name, idx)) in suggestions.iter().zip(columns) {
+        let examples: Vec<String> = suggestion.counter_examples.iter().map(|e| truncate_string(e, maxlen)).collect();
+        println!(
+            "col_{:05}_{}\t{:<24}\t{:<8}\t{:<8}\t{:<8}\t{}",
+            idx,
+            name,
+            suggestion.inferred.to_string(),
+            format!("{:.1}%", suggestion.confidence * 100.0),
+            if suggestion.nullable { "yes" } else { "no" },
+            suggestion.max_length,
+            examples.join(",")
+        );
+    }
+}
+
+/// Date formats matched per column, with the chosen column format first
+fn print_dates(date_inference: &HashMap<String, DateInference>, columns: &[(String, usize)]) {
+    println!();
+    println!("Dates:");
+    println!("{:<32}\t{:<24}\t{:<8}\t{:<32}", "column", "format", "matched", "note");
+    println!("{:-<32}\t{:-<24}\t{:-<8}\t{:-<32}", "", "", "", "");
+    with_date_parser(|parser| {
+        for (name, idx) in columns {
+            let inference = match date_inference.get(name) {
+                Some(inference) if inference.parsed > 0 => inference,
+                _ => continue,
+            };
+            for (rank, (format, count)) in inference.ranked_formats(parser).into_iter().enumerate() {
+                let note = if rank == 0 {
+                    format!(
+                        "chosen; parsed {} of {}, {} ambiguous (DMY/MDY)",
+                        inference.parsed, inference.values, inference.ambiguous
+                    )
+                } else {
+                    String::new()
+                };
+                println!("col_{:05}_{}\t{:<24}\t{:<8}\t{}", idx, name, format, count, note);
+            }
+        }
+    });
+}
+
+/// Per-country match rates for postal code columns, most likely country first,
+/// with the values that fit no country
+fn print_postcodes(postcode_profiles: &HashMap<String, PostcodeProfile>, columns: &[(String, usize)], maxlen: usize) {
+    println!();
+    println!("Postcodes:");
+    println!("{:<32}\t{:<8}\t{:<8}\t{:<32}", "column", "country", "rate", "note");
+    println!("{:-<32}\t{:-<8}\t{:-<8}\t{:-<32}", "", "", "", "");
+    for (name, idx) in columns {
+        let profile = match postcode_profiles.get(name) {
+            Some(profile) if profile.values > 0 => profile,
+            _ => continue,
+        };
+        for (rank, (country, rate)) in profile.ranked().into_iter().enumerate() {
+            let note = if rank == 0 { "most likely" } else { "" };
+            println!("col_{:05}_{}\t{:<8}\t{:<8}\t{}", idx, name, country, format!("{:.1}%", rate * 100.0), note);
+        }
+        if profile.unmatched > 0 {
+            let examples: Vec<String> = profile.unmatched_examples.iter().map(|e| truncate_string(e, maxlen)).collect();
+            println!(
+                "col_{:05}_{}\t{:<8}\t{:<8}\t{} of {} match no country:{}",
+                idx,
+                name,
+                "none",
+                format!("{:.1}%", profile.unmatched as f64 / profile.values as f64 * 100.0),
+                profile.unmatched,
+                profile.values,
+                examples.join(",")
+            );
+        }
+    }
+}
+
+/// Per-column assertion pass rates, value coverage of standardised outputs
+/// and sampled failures
+fn print_scorecard(scorecard: &Scorecard, columns: &[(String, usize)], maxlen: usize) {
+    println!();
+    println!("Scorecard:");
+    println!(
+        "{:<32}\t{:<24}\t{:<8}\t{:<24}\t{:<32}",
+        "column", "check", "rate", "result", "failures"
+    );
+    println!("{:-<32}\t{:-<24}\t{:-<8}\t{:-<24}\t{:-<32}", "", "", "", "", "");
+    let examples = |values: &[String]| {
+        values
+            .iter()
+            .map(|v| truncate_string(v, maxlen))
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+    for (name, idx) in columns {
+        let rules = match scorecard.column(name) {
+            Some(rules) => rules,
+            None => continue,
+        };
+        for (rule, score) in rules {
+            if score.keys.is_empty() {
+                // The rule applied but never produced a result, e.g. no date parsed
+                println!(
+                    "col_{:05}_{}\t{:<24}\t{:<8}\t{:<24}\t{}",
+                    idx, name, rule, "0.0%", format!("0 of {}", score.applied),
+                    examples(&score.no_result_examples)
+                );
+                continue;
+            }
+            for (key, key_score) in &score.keys {
+                let check = if key == rule { key.clone() } else { format!("{}.{}", rule, key) };
+                let (result, failures) = if key_score.is_assertion() {
+                    (format!("true {} / false {}", key_score.passed, key_score.failed), examples(&key_score.failures))
+                } else if let (Some(min), Some(max)) = (key_score.min, key_score.max) {
+                    (format!("min {} / max {}", min, max), String::new())
+                } else {
+                    (format!("{} of {}", key_score.produced, score.applied), examples(&score.no_result_examples))
+                };
+                println!(
+                    "col_{:05}_{}\t{:<24}\t{:<8}\t{:<24}\t{}",
+                    idx, name, check, format!("{:.1}%", key_score.rate(score.applied)), result, failures
+                );
+            }
+        }
+    }
+}
+
+/// Violations of an expectations file, one per line, then the overall result
+fn print_validation(validation: &Validation, expectations_path: &str, rows: usize) {
+    println!();
+    println!("Validation Report: {}", Local::now().format("%Y%m%d %H:%M:%S"));
+    println!("Expectations: {}", expectations_path);
+    println!("Examined rows: {}", rows);
+    println!();
+    if !validation.passed() {
+        println!("{:<32}\t{:<24}\t{:<32}", "column", "check", "violation");
+        println!("{:-<32}\t{:-<24}\t{:-<32}", "", "", "");
+        for violation in &validation.violations {
+            println!("{}", violation);
+        }
+        println!();
+    }
+    println!(
+        "Result: {} ({} of {} checks failed)",
+        if validation.passed() { "PASSED" } else { "FAILED" },
+        validation.violations.len(),
+        validation.checks
+    );
+}
+
+/// The active rules' results for one value, as the enhanced output computes them
+fn rule_results(field_name: &str, raw: &str) -> serde_json::Map<String, Value> {
+    let hu = mask_value(raw, "HU", field_name);
+    let lu = mask_value(raw, "LU", field_name);
+    match with_active_rules(|rules| rules.evaluate(field_name, raw, &lu, &hu)) {
+        Value::Object(results) => results,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/// A row's fields after replacements, and its appended values by column name
+/// along with the index of the column each came from
+type CleansedRow = (Vec<String>, HashMap<String, (usize, String)>);
+
+/// Write the input back out with standardised values from the rules: tabular,
+/// fixed-width and Excel input as delimited text with appended columns after
+/// the original ones, everything else as NDJSON. Records keep their order.
+fn cleanse_output(
+    cleanser: &Cleanser,
+    lines: &[String],
+    excel_rows: Vec<Vec<String>>,
+    format: &str,
+    delimiter: u8,
+    header_row: usize,
+    fixed_layout: Option<&FixedWidthLayout>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (header, rows): (Vec<String>, Vec<Vec<String>>) = match format {
+        "excel" => {
+            let mut rows = excel_rows;
+            let data = rows.split_off((header_row + 1).min(rows.len()));
+            (rows.pop().unwrap_or_default(), data)
+        }
+        "fixed" => {
+            let layout = fixed_layout.ok_or("--layout is required when format is 'fixed'")?;
+            (layout.column_names(), lines.iter().filter(|l| !l.is_empty()).map(|l| layout.slice(l)).collect())
+        }
+        "tabular" => (
+            lines.get(header_row).map(|l| parse_csv_line(l, delimiter)).unwrap_or_default(),
+            lines.iter().skip(header_row + 1).filter(|l| !l.is_empty()).map(|l| parse_csv_line(l, delimiter)).collect(),
+        ),
+        _ => {
+            let records: Vec<(&String, Option<Value>)> = lines
+                .par_iter()
+                .filter(|line| !line.is_empty())
+                .map(|line| (line, serde_json::from_str::<Value>(line).ok()))
+                .collect();
+            set_column_date_formats(records.iter().filter_map(|(_, record)| record.as_ref()).flat_map(|record| {
+                let mut fields = Vec::new();
+                json_rule_fields(record, "", &mut fields);
+                fields
+            }));
+            let cleansed: Vec<String> = records
+                .into_par_iter()
+                .map(|(line, record)| match record {
+                    Some(_) => cleanser.cleanse_json(line, &rule_results).unwrap_or_else(|_| line.clone()),
+                    // Lines that are not JSON are passed through untouched
+                    None => line.clone(),
+                })
+                .collect();
+            for line in cleansed {
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+    };
+
+    // Column names as the DQ report and the rules see them
+    let names: Vec<String> = header.iter().map(|s| s.trim().replace(" ", "_")).collect();
+    set_column_date_formats(rows.iter().flat_map(|fields| names.iter().cloned().zip(fields.iter().cloned())));
+    let cleansed: Vec<CleansedRow> = rows
+        .into_par_iter()
+        .map(|mut fields| {
+            let mut appended = HashMap::new();
+            for (idx, (field, name)) in fields.iter_mut().zip(&names).enumerate() {
+                let changes = cleanser.changes(name, &rule_results(name, field));
+                if let Some(replacement) = changes.replace {
+                    *field = value_text(&replacement);
+                }
+                for (column, value) in changes.append {
+                    appended.insert(column, (idx, value_text(&value)));
+                }
             }
+            (fields, appended)
+        })
+        .collect();
+
+    // Appended columns follow the original ones, ordered by the column they came from
+    let appended_columns: BTreeMap<(usize, &String), ()> = cleansed
+        .iter()
+        .flat_map(|(_, appended)| appended.iter().map(|(name, (idx, _))| ((*idx, name), ())))
+        .collect();
+    let appended_names: Vec<String> = appended_columns.keys().map(|(_, name)| name.to_string()).collect();
+    // An appended column never shares a name with an original one
+    let mut taken: HashSet<String> = header.iter().cloned().collect();
+    let output_names: Vec<String> = appended_names
+        .iter()
+        .map(|name| {
+            let output = unique_name(name, &taken);
+            taken.insert(output.clone());
+            output
+        })
+        .collect();
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(io::stdout());
+    writer.write_record(header.iter().chain(output_names.iter()))?;
+    for (mut fields, appended) in cleansed {
+        // Ragged rows: short ones are padded, extra fields are kept after the appended columns
+        let extra = if fields.len() > header.len() { fields.split_off(header.len()) } else { Vec::new() };
+        fields.resize(header.len(), String::new());
+        let appended_values = appended_names
+            .iter()
+            .map(|name| appended.get(name).map(|(_, value)| value.clone()).unwrap_or_default());
+        writer.write_record(fields.into_iter().chain(appended_values).chain(extra))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Built-in assertion rules plus any from --rules-file, narrowed by
+/// --enable-rules and --disable-rules
+fn configure_rules(matches: &clap::ArgMatches) -> Result<RuleRegistry, String> {
+    let list = |name: &str| -> Vec<&str> {
+        matches
+            .value_of(name)
+            .map(|value| value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    };
+    let mut registry = RuleRegistry::with_builtin_rules();
+    if let Some(path) = matches.value_of("rules_file") {
+        let rules = load_rules_file(path).map_err(|e| format!("{}: {}", path, e))?;
+        for rule in rules {
+            registry.try_register(rule).map_err(|e| format!("{}: {}", path, e))?;
         }
+    }
+    if matches.is_present("enable_rules") {
+        registry.enable_only(&list("enable_rules"))?;
+    }
+    registry.disable(&list("disable_rules"))?;
+    Ok(registry)
+}
+
+/// Excel formula reading and region selection from the command line
+fn excel_options(matches: &clap::ArgMatches) -> ExcelOptions {
+    let region = if let Some(table) = matches.value_of("table") {
+        Some(ExcelRegion::Table(table.to_string()))
+    } else if let Some(name) = matches.value_of("named_range") {
+        Some(ExcelRegion::NamedRange(name.to_string()))
     } else {
-        vec![]
+        matches.value_of("range").map(|range| match range {
+            "auto" => ExcelRegion::Auto,
+            reference => ExcelRegion::Range(reference.to_string()),
+        })
+    };
+    ExcelOptions {
+        formulas: matches.is_present("formulas"),
+        region,
     }
 }
 
+/// Parquet column, row group and row limit selection from the command line
+fn parquet_options(matches: &clap::ArgMatches) -> Result<ParquetOptions, String> {
+    let list = |name: &str| {
+        matches.value_of(name).map(|value| {
+            value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<String>>()
+        })
+    };
+    let row_groups = match list("row_groups") {
+        Some(groups) => Some(
+            groups
+                .iter()
+                .map(|g| g.parse().map_err(|_| format!("--row-groups must be comma-separated numbers, not '{}'", g)))
+                .collect::<Result<Vec<usize>, String>>()?,
+        ),
+        None => None,
+    };
+    let row_limit = match matches.value_of("row_limit") {
+        Some(n) => Some(n.parse().map_err(|_| format!("--row-limit must be a number, not '{}'", n))?),
+        None => None,
+    };
+    Ok(ParquetOptions {
+        columns: list("columns"),
+        row_groups,
+        row_limit,
+    })
+}
 
+/// Parquet metadata report: schema, row groups and the footer statistics of
+/// each leaf column, printed next to what bytefreq finds in the same column.
+fn parquet_metadata_report(
+    path: &str,
+    options: &ParquetOptions,
+    grain: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let summary = ParquetReader::describe(path, options)?;
+    let mask = |value: &str, field: &str| mask_value(value, grain, field);
+    // Every leaf is profiled so each footer column has its findings
+    let profile = ParquetReader::profile_columns(path, options, true, usize::MAX, &mask)?;
+    let profiled_rows = profile.rows;
+    let findings: HashMap<String, _> = profile
+        .into_columns()
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+    let now = Local::now();
+    println!();
+    println!("Parquet Metadata Report: {}", now.format("%Y%m%d %H:%M:%S"));
+    println!("File: {}", path);
+    println!("Created by: {}", summary.created_by.as_deref().unwrap_or("-"));
+    println!("Rows: {} in {} row groups (profiled: {})", summary.rows, summary.row_groups.len(), profiled_rows);
+
+    println!();
+    println!("RowGroups:");
+    println!("{:<8}\t{:<12}\t{:<12}\t{:<12}", "index", "rows", "compressed", "uncompressed");
+    println!("{:-<8}\t{:-<12}\t{:-<12}\t{:-<12}", "", "", "", "");
+    for group in &summary.row_groups {
+        println!(
+            "{:<8}\t{:<12}\t{:<12}\t{:<12}",
+            group.index, group.rows, group.compressed_bytes, group.uncompressed_bytes
+        );
+    }
 
+    println!();
+    println!("Schema:");
+    println!("{:<32}\t{:<32}\t{:<12}\t{:<24}\t{:<12}\t{:<12}", "column", "parquet_path", "physical", "logical", "compression", "bytes");
+    println!("{:-<32}\t{:-<32}\t{:-<12}\t{:-<24}\t{:-<12}\t{:-<12}", "", "", "", "", "", "");
+    for column in &summary.columns {
+        println!(
+            "{:<32}\t{:<32}\t{:<12}\t{:<24}\t{:<12}\t{:<12}",
+            column.path, column.parquet_path, column.physical_type, column.logical_type,
+            column.compression, column.compressed_bytes
+        );
+    }
 
+    // Statistics cover whole row groups even when --row-limit is used
+    println!();
+    println!("Statistics:");
+    println!(
+        "{:<32}\t{:<8}\t{:<8}\t{:<8}\t{:<24}\t{:<24}\t{:<8}\t{:<16}\t{:<24}",
+        "column", "nulls", "bf_nulls", "distinct", "min", "max", "masks", "top_mask", "example"
+    );
+    println!("{:-<32}\t{:-<8}\t{:-<8}\t{:-<8}\t{:-<24}\t{:-<24}\t{:-<8}\t{:-<16}\t{:-<24}", "", "", "", "", "", "", "", "", "");
+    let opt = |value: Option<u64>| value.map_or("-".to_string(), |v| v.to_string());
+    for column in &summary.columns {
+        let (bf_nulls, masks, top_mask, example) = match findings.get(&column.path) {
+            Some(found) => {
+                let top = found.counts.iter().max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)));
+                let top_mask = top.map_or(String::new(), |(mask, count)| format!("{} ({})", mask, count));
+                let example = top
+                    .and_then(|(mask, _)| found.examples.get(mask))
+                    .map_or(String::new(), |e| truncate_string(e, 24));
+                (found.nulls.to_string(), found.counts.len().to_string(), top_mask, example)
+            }
+            None => ("-".to_string(), "-".to_string(), String::new(), String::new()),
+        };
+        println!(
+            "{:<32}\t{:<8}\t{:<8}\t{:<8}\t{:<24}\t{:<24}\t{:<8}\t{:<16}\t{:<24}",
+            column.path,
+            opt(column.null_count),
+            bf_nulls,
+            opt(column.distinct_count),
+            truncate_string(column.min.as_deref().unwrap_or("-"), 24),
+            truncate_string(column.max.as_deref().unwrap_or("-"), 24),
+            masks,
+            top_mask,
+            example
+        );
+    }
+    Ok(())
+}
 
-fn main() {
+/// Per-column Excel findings printed next to the mask patterns: the types
+/// cells were stored as, error values, masked formulas and the sheet layout
+struct ExcelDiagnostics {
+    cell_types: Vec<BTreeMap<CellType, usize>>,
+    errors: Vec<BTreeMap<String, usize>>,
+    formulas: Vec<ColumnPatterns>,
+    layout: Option<SheetOverview>,
+}
 
-    // Setup Rayon
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(22) // Using 22 cores out of 24.
-        .build_global()
-        .unwrap();
+impl ExcelDiagnostics {
+    /// Summarise the rows below the header row
+    fn new(rows: &[Vec<ExcelCell>], header_row: usize, grain: &str, layout: Option<SheetOverview>) -> Self {
+        let mut diagnostics = ExcelDiagnostics {
+            cell_types: Vec::new(),
+            errors: Vec::new(),
+            formulas: Vec::new(),
+            layout,
+        };
+        let mut rng = thread_rng();
+        for row in rows.iter().skip(header_row + 1) {
+            for (idx, cell) in row.iter().enumerate() {
+                if diagnostics.cell_types.len() <= idx {
+                    diagnostics.cell_types.resize(idx + 1, BTreeMap::new());
+                    diagnostics.errors.resize(idx + 1, BTreeMap::new());
+                    diagnostics.formulas.resize(idx + 1, ColumnPatterns::default());
+                }
+                *diagnostics.cell_types[idx].entry(cell.cell_type).or_insert(0) += 1;
+                if cell.cell_type == CellType::Error {
+                    *diagnostics.errors[idx].entry(cell.value.clone()).or_insert(0) += 1;
+                }
+                if let Some(formula) = &cell.formula {
+                    let column = &mut diagnostics.formulas[idx];
+                    let masked = mask_value(formula, grain, "");
+                    let count = column.counts.entry(masked.clone()).or_insert(0);
+                    *count += 1;
+
+                    // Reservoir sampling
+                    if rng.gen::<f64>() < 1.0 / (*count as f64) {
+                        column.examples.insert(masked, format!("={}", formula));
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
 
-    let matches = App::new("Bytefreq Data Profiler")
-        .version("1.0")
-        .author("Andrew Morgan <minkymorgan@gmail.com>\n")
-        .about("A command-line tool to generate data profiling reports based on various masking strategies.")
-        .arg(
-            Arg::new("grain")
+    fn print(&self, names: &[String], maxlen: usize) {
+        let name = |idx: usize| names.get(idx).map(|n| n.as_str()).unwrap_or("");
+
+        println!();
+        println!("CellTypes:");
+        println!("{:<32}\t{:<32}", "column", "types");
+        println!("{:-<32}\t{:-<32}", "", "");
+        for (idx, counts) in self.cell_types.iter().enumerate() {
+            let typed = counts.keys().filter(|t| **t != CellType::Empty).count();
+            println!(
+                "col_{:05}_{}\t{}{}",
+                idx, name(idx), Self::tally(counts.iter().map(|(t, c)| (t.label(), c))),
+                if typed > 1 { " (mixed)" } else { "" }
+            );
+        }
+
+        if self.errors.iter().any(|e| !e.is_empty()) {
+            println!();
+            println!("ErrorCells:");
+            println!("{:<32}\t{:<32}", "column", "errors");
+            println!("{:-<32}\t{:-<32}", "", "");
+            for (idx, counts) in self.errors.iter().enumerate().filter(|(_, e)| !e.is_empty()) {
+                println!("col_{:05}_{}\t{}", idx, name(idx), Self::tally(counts.iter().map(|(e, c)| (e.as_str(), c))));
+            }
+        }
+
+        if self.formulas.iter().any(|f| !f.counts.is_empty()) {
+            println!();
+            println!("Formulas:");
+            println!("{:<32}\t{:<8}\t{:<8}\t{:<32}", "column", "count", "pattern", "example");
+            println!("{:-<32}\t{:-<8}\t{:-<8}\t{:-<32}", "", "", "", "");
+            for (idx, column) in self.formulas.iter().enumerate() {
+                let mut counts: Vec<(&String, &usize)> = column.counts.iter().collect();
+                counts.sort_unstable_by(|a, b| b.1.cmp(a.1));
+                for (pattern, count) in counts {
+                    let example = column.examples.get(pattern).map(|e| e.as_str()).unwrap_or("");
+                    println!(
+                        "col_{:05}_{}\t{:<8}\t{:<8}\t{:<32}",
+                        idx, name(idx), count, pattern, truncate_string(example, maxlen)
+                    );
+                }
+            }
+        }
+
+        if let Some(layout) = &self.layout {
+            let list = |items: Vec<String>| if items.is_empty() { "-".to_string() } else { items.join(" ") };
+            println!();
+            println!("SheetLayout:");
+            println!("Used range: {}", layout.dimensions.as_deref().unwrap_or("-"));
+            println!("Merged regions: {}", list(layout.merged_regions.clone()));
+            println!("Hidden rows: {}", list(layout.hidden_rows.iter().map(|r| (r + 1).to_string()).collect()));
+            println!("Hidden columns: {}", list(layout.hidden_cols.iter().map(|c| column_letters(*c)).collect()));
+        }
+    }
+
+    fn tally<'a>(counts: impl Iterator<Item = (&'a str, &'a usize)>) -> String {
+        counts
+            .map(|(label, count)| format!("{} {}", label, count))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// Profile several sheets of a workbook in one run: a workbook overview
+/// followed by a DQ report per sheet. Each sheet uses `header_row` when given,
+/// otherwise the header row guessed from its layout.
+fn excel_workbook_report(
+    path: &str,
+    pattern: Option<&str>,
+    header_row: Option<usize>,
+    options: &ExcelOptions,
+    grain: &str,
+    maxlen: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let overview = ExcelReader::workbook_overview(path)?;
+    let selected = ExcelReader::matching_sheet_names(path, pattern)?;
+    if selected.is_empty() {
+        return Err(format!("No sheets match '{}'", pattern.unwrap_or("*")).into());
+    }
+
+    let now = Local::now();
+    println!();
+    println!("Data Profiling Report: {}", now.format("%Y%m%d %H:%M:%S"));
+    println!("Workbook: {}", path);
+    println!();
+    println!("WorkbookOverview:");
+    println!(
+        "{:<32}\t{:<8}\t{:<12}\t{:<8}\t{:<8}\t{:<10}\t{:<8}\t{:<8}\t{:<24}",
+        "sheet", "visible", "range", "rows", "cols", "header_row", "errors", "hidden", "merged"
+    );
+    println!("{:-<32}\t{:-<8}\t{:-<12}\t{:-<8}\t{:-<8}\t{:-<10}\t{:-<8}\t{:-<8}\t{:-<24}", "", "", "", "", "", "", "", "", "");
+    for sheet in &overview {
+        let merged = if sheet.merged_regions.is_empty() {
+            "-".to_string()
+        } else {
+            format!("{} ({})", sheet.merged_regions.len(), sheet.merged_regions.join(" "))
+        };
+        let hidden = if sheet.hidden_rows.is_empty() && sheet.hidden_cols.is_empty() {
+            "-".to_string()
+        } else {
+            format!("{}r {}c", sheet.hidden_rows.len(), sheet.hidden_cols.len())
+        };
+        println!(
+            "{:<32}\t{:<8}\t{:<12}\t{:<8}\t{:<8}\t{:<10}\t{:<8}\t{:<8}\t{:<24}",
+            sheet.name,
+            if sheet.visible { "yes" } else { "no" },
+            sheet.dimensions.as_deref().unwrap_or("-"),
+            sheet.rows,
+            sheet.cols,
+            sheet.header_row,
+            sheet.error_cells,
+            hidden,
+            merged
+        );
+    }
+
+    for sheet in overview.iter().filter(|sheet| selected.contains(&sheet.name)) {
+        println!();
+        println!("Sheet: {}", sheet.name);
+        let rows = match ExcelReader::read_cells_by_name_with(path, &sheet.name, options) {
+            Ok(rows) => rows,
+            Err(e) => {
+                println!("Skipped: {}", e);
+                continue;
+            }
+        };
+        // The overview's guess is for the whole used range, not a selected region
+        let header_row = match (header_row, &options.region) {
+            (Some(row), _) => row,
+            (None, Some(_)) => guess_header_row(&rows),
+            (None, None) => sheet.header_row,
+        };
+
+        let mut columns: Vec<ColumnPatterns> = rows
+            .get(header_row)
+            .map(|header| {
+                header
+                    .iter()
+                    .map(|cell| ColumnPatterns {
+                        name: cell.value.trim().replace(" ", "_"),
+                        ..Default::default()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut field_counts: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut rng = thread_rng();
+        for row in rows.iter().skip(header_row + 1) {
+            *field_counts.entry(row.len()).or_insert(0) += 1;
+            for (idx, cell) in row.iter().enumerate() {
+                if columns.len() <= idx {
+                    let name = format!("RaggedErr{}", idx + 1 - columns.len());
+                    columns.push(ColumnPatterns { name, ..Default::default() });
+                }
+                let column = &mut columns[idx];
+                let masked = mask_value(&cell.value, grain, &column.name);
+                let count = column.counts.entry(masked.clone()).or_insert(0);
+                *count += 1;
+
+                // Reservoir sampling
+                if rng.gen::<f64>() < 1.0 / (*count as f64) {
+                    column.examples.insert(masked, cell.value.clone());
+                }
+            }
+        }
+
+        println!("Header row: {}", header_row);
+        println!("Examined rows: {}", rows.len().saturating_sub(header_row + 1));
+        print_fields_per_line(&field_counts);
+
+        let names: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+        ExcelDiagnostics::new(&rows, header_row, grain, Some(sheet.clone())).print(&names, maxlen);
+
+        print_pattern_table(
+            columns
+                .iter()
+                .enumerate()
+                .map(|(idx, column)| (column.name.as_str(), idx, &column.counts, &column.examples)),
+            maxlen,
+        );
+    }
+    Ok(())
+}
+
+/// Number of rows per number of fields, fewest fields first
+fn print_fields_per_line<'a>(field_counts: impl IntoIterator<Item = (&'a usize, &'a usize)>) {
+    let mut field_counts: Vec<(&usize, &usize)> = field_counts.into_iter().collect();
+    field_counts.sort_unstable();
+    println!();
+    println!("FieldsPerLine:");
+    for (field_count, frequency) in field_counts {
+        println!("{} fields: {} rows", field_count, frequency);
+    }
+}
+
+/// The masks of each column, most frequent first, with an example of each.
+/// Columns are given as (name, index, mask counts, examples by mask).
+fn print_pattern_table<'a>(
+    columns: impl IntoIterator<Item = (&'a str, usize, &'a HashMap<String, usize>, &'a HashMap<String, String>)>,
+    maxlen: usize,
+) {
+    println!();
+    println!(
+        "{:<32}\t{:<8}\t{:<8}\t{:<32}",
+        "column", "count", "pattern", "example"
+    );
+    println!("{:-<32}\t{:-<8}\t{:-<8}\t{:-<32}", "", "", "", "");
+    for (name, idx, counts, examples) in columns {
+        let mut column_counts: Vec<(&String, &usize)> = counts.iter().collect();
+        column_counts.sort_unstable_by(|a, b| b.1.cmp(a.1));
+        for (value, count) in column_counts {
+            let example = examples.get(value).map(|e| e.as_str()).unwrap_or("");
+            println!(
+                "col_{:05}_{}\t{:<8}\t{:<8}\t{:<32}",
+                idx, name, count, value, truncate_string(example, maxlen)
+            );
+        }
+    }
+}
+
+/// Parse a CSV line using proper CSV quoting rules
+fn parse_csv_line(line: &str, delimiter: u8) -> Vec<String> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .from_reader(line.as_bytes());
+
+    if let Some(result) = reader.records().next() {
+        match result {
+            Ok(record) => record.iter().map(|s| s.to_string()).collect(),
+            Err(_) => {
+                // Fallback to naive splitting if CSV parsing fails
+                line.split(delimiter as char).map(|s| s.to_string()).collect()
+            }
+        }
+    } else {
+        vec![]
+    }
+}
+
+
+
+
+
+fn main() {
+
+    // Setup Rayon
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(22) // Using 22 cores out of 24.
+        .build_global()
+        .unwrap();
+
+    let matches = App::new("Bytefreq Data Profiler")
+        .version("1.0")
+        .author("Andrew Morgan <minkymorgan@gmail.com>\n")
+        .about("A command-line tool to generate data profiling reports based on various masking strategies.")
+        .arg(
+            Arg::new("grain")
             .short('g')
             .long("grain")
             .value_name("GRAIN")
@@ -712,7 +1699,11 @@ fn main() {
                    'json' - JSON data (each line should contain a JSON object)\n\
                    'tabular' - Tabular data (first line should be the header)\n\
                    'excel' - Excel file (.xlsx, .xls, .xlsb, .ods) - requires --excel-path\n\
-                   'parquet' - Parquet file (.parquet) - requires --parquet-path")
+                   'parquet' - Parquet file (.parquet) - requires --parquet-path\n\
+                   'fixed' - Fixed-width records (no header line) - requires --layout\n\
+                   'xml' - XML document, one record per element (see --xml-record)\n\
+                   'avro' - Avro object container file - --avro-path or stdin\n\
+                   'arrow' / 'feather' - Arrow IPC file or stream - --arrow-path or stdin")
                 .takes_value(true)
                 .default_value("tabular"),
         )
@@ -730,6 +1721,58 @@ fn main() {
                 .help("Path to Parquet file (required when format is 'parquet')")
                 .takes_value(true),
         )
+        .arg(
+            Arg::new("columns")
+                .long("columns")
+                .value_name("COLUMNS")
+                .help("Comma-separated Parquet columns to read, by name or dotted path (e.g. 'id,user.address')")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("row_groups")
+                .long("row-groups")
+                .value_name("ROW_GROUPS")
+                .help("Comma-separated 0-based Parquet row group indices to read")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("row_limit")
+                .long("row-limit")
+                .value_name("ROWS")
+                .help("Read at most this many rows from a Parquet file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("avro_path")
+                .long("avro-path")
+                .value_name("AVRO_PATH")
+                .help("Path to Avro object container file (format 'avro'; reads stdin if omitted)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("arrow_path")
+                .long("arrow-path")
+                .value_name("ARROW_PATH")
+                .help("Path to Arrow IPC file/stream or Feather v2 file (format 'arrow'; reads stdin if omitted)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("layout")
+                .long("layout")
+                .value_name("LAYOUT")
+                .help("Column layout for fixed-width data (required when format is 'fixed').\n\
+                       Either inline 'name:start:width,...' with 1-based start positions,\n\
+                       or a file with one 'name start width' per line, or a COBOL copybook.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("xml_record")
+                .long("xml-record")
+                .value_name("ELEMENT")
+                .help("Element name that delimits records in XML input.\n\
+                       Default: each child of the root element is a record.")
+                .takes_value(true),
+        )
         .arg(
             Arg::new("sheet")
                 .short('s')
@@ -746,6 +1789,51 @@ fn main() {
                 .help("Sheet name to process (overrides --sheet if provided)")
                 .takes_value(true),
         )
+        .arg(
+            Arg::new("formulas")
+                .long("formulas")
+                .help("Excel: also read the formula behind each cell and report formula patterns per column")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("range")
+                .long("range")
+                .value_name("A1_RANGE")
+                .help("Excel: read only this range of the sheet, e.g. 'B5:K2000'.\n\
+                       'auto' selects the largest contiguous block of non-empty cells.\n\
+                       --header-row counts from the top of the range")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("table")
+                .long("table")
+                .value_name("TABLE")
+                .help("Excel: read a defined Table (ListObject) by name, header included (xlsx only)")
+                .takes_value(true)
+                .conflicts_with_all(&["range", "named_range", "all_sheets", "sheets"]),
+        )
+        .arg(
+            Arg::new("named_range")
+                .long("named-range")
+                .value_name("NAME")
+                .help("Excel: read a workbook named range; its sheet is used regardless of --sheet")
+                .takes_value(true)
+                .conflicts_with_all(&["range", "all_sheets", "sheets"]),
+        )
+        .arg(
+            Arg::new("all_sheets")
+                .long("all-sheets")
+                .help("Profile every sheet of the workbook, with a workbook overview.\n\
+                       Each sheet uses its guessed header row unless --header-row is given")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("sheets")
+                .long("sheets")
+                .value_name("GLOB")
+                .help("Profile the sheets whose names match a glob, e.g. 'Sales*' (implies --all-sheets)")
+                .takes_value(true),
+        )
         .arg(
             Arg::new("header_row")
                 .long("header-row")
@@ -761,7 +1849,11 @@ fn main() {
         .value_name("REPORT")
         .help("Sets the type of report to generate:\n\
                'DQ' - Data Quality (default)\n\
-               'CP' - Character Profiling")
+               'CP' - Character Profiling\n\
+               'CPC' - Character Profiling per Column (tabular or json)\n\
+               'META' - Parquet metadata and statistics alongside bytefreq findings (parquet only)\n\
+               'VALIDATE' - Check the DQ profile against --expectations; exits 1 on violations\n\
+               'LEARN' - Write expectations (YAML) describing the input, for later VALIDATE runs")
         .takes_value(true)
         .default_value("DQ"),
     )
@@ -806,6 +1898,143 @@ fn main() {
          .value_name("FLAT_ENHANCED")
          .help("Formats the enhanced output in a flattened format")
          .takes_value(false)
+    )
+    .arg(
+        Arg::new("cleanse")
+        .long("cleanse")
+        .help("Write the input back out (delimited or NDJSON) with standardised values from the rules appended or substituted")
+        .takes_value(false)
+        .conflicts_with_all(&["enhanced_output", "flat_enhanced"])
+    )
+    .arg(
+        Arg::new("cleanse_map")
+        .long("cleanse-map")
+        .value_name("FILE")
+        .help("Mapping of columns to rule outputs for --cleanse (YAML, or TOML for .toml); without it every std_* value is appended")
+        .takes_value(true)
+        .requires("cleanse")
+    )
+    .arg(
+        Arg::new("treatments")
+        .long("treatments")
+        .value_name("FILE")
+        .help("Treatments keyed on column and mask, applied in enhanced output (YAML, or TOML for .toml)")
+        .takes_value(true)
+    )
+    .arg(
+        Arg::new("dates")
+        .long("dates")
+        .help("Add a report section with the date formats each column fits, day/month ambiguity and the chosen column format")
+        .takes_value(false)
+    )
+    .arg(
+        Arg::new("postcodes")
+        .long("postcodes")
+        .help("Add a report section with per-country postal code match rates for post* and zip* columns, and the values that fit no country")
+        .takes_value(false)
+    )
+    .arg(
+        Arg::new("types")
+        .long("types")
+        .help("Add a report section recommending a type per column, with confidence and counter-examples")
+        .takes_value(false)
+    )
+    .arg(
+        Arg::new("schema")
+        .long("schema")
+        .value_name("KIND")
+        .help("Write the recommended types as a schema instead of the report: 'sql' (CREATE TABLE) or 'arrow' (Arrow schema JSON)")
+        .takes_value(true)
+        .possible_values(["sql", "arrow"])
+    )
+    .arg(
+        Arg::new("schema_table")
+        .long("schema-table")
+        .value_name("NAME")
+        .help("Table name for --schema sql")
+        .takes_value(true)
+        .default_value("data")
+    )
+    .arg(
+        Arg::new("date_order")
+        .long("date-order")
+        .value_name("ORDER")
+        .help("How to read dates that fit both day-first and month-first formats: DMY, MDY or YMD")
+        .takes_value(true)
+        .default_value("DMY")
+    )
+    .arg(
+        Arg::new("date_format")
+        .long("date-format")
+        .value_name("FORMAT")
+        .help("Extra chrono date format to try before the built-in ones, e.g. '%d %b %y'; may be repeated")
+        .takes_value(true)
+        .multiple_occurrences(true)
+    )
+    .arg(
+        Arg::new("expected_chars")
+        .long("expected-chars")
+        .value_name("CHARSET")
+        .help("Characters considered normal in the CPC report; anything else is listed per column.\n\
+               Presets: 'ascii' (printable ASCII), 'latin1', 'alnum', or a custom set such as 'a-zA-Z0-9 .,\\-'")
+        .takes_value(true)
+        .default_value("ascii")
+    )
+    .arg(
+        Arg::new("enable_rules")
+        .long("enable-rules")
+        .value_name("RULES")
+        .help("Comma-separated assertion rules to run in enhanced output; all others are disabled")
+        .takes_value(true)
+    )
+    .arg(
+        Arg::new("disable_rules")
+        .long("disable-rules")
+        .value_name("RULES")
+        .help("Comma-separated assertion rules to skip in enhanced output")
+        .takes_value(true)
+    )
+    .arg(
+        Arg::new("scorecard")
+        .long("scorecard")
+        .help("Run the assertion rules during the DQ report and add a per-column scorecard of results")
+        .takes_value(false)
+    )
+    .arg(
+        Arg::new("expectations")
+        .long("expectations")
+        .value_name("FILE")
+        .help("Expectations file (YAML, or TOML for .toml) checked by the VALIDATE report")
+        .takes_value(true)
+    )
+    .arg(
+        Arg::new("min_mask_share")
+        .long("min-mask-share")
+        .value_name("SHARE")
+        .help("LEARN: masks covering less than this share of a column are left out of allowed_masks")
+        .takes_value(true)
+        .default_value("0.01")
+    )
+    .arg(
+        Arg::new("rules_file")
+        .long("rules-file")
+        .value_name("FILE")
+        .help("YAML (or .toml) file of extra assertion rules for enhanced output")
+        .takes_value(true)
+    )
+    .arg(
+        Arg::new("list_rules")
+        .long("list-rules")
+        .help("List the assertion rules and exit")
+        .takes_value(false)
+    )
+    .arg(
+        Arg::new("files")
+        .value_name("FILE")
+        .help("Input files for the 'tabular', 'json' and 'fixed' formats, read in parallel.\n\
+               Reads stdin when omitted. gzip, zstd, bzip2 and xz input is decompressed transparently")
+        .multiple_values(true)
+        .index(1)
     )
         .get_matches();
 
@@ -815,14 +2044,142 @@ fn main() {
     let flat_enhanced = matches.is_present("flat_enhanced");
     let remove_array_numbers = matches.is_present("remove_array_numbers");
 
+    match configure_rules(&matches) {
+        Ok(registry) => {
+            if matches.is_present("list_rules") {
+                for (name, description, enabled) in registry.rules() {
+                    println!("{:<24}\t{:<8}\t{}", name, if enabled { "enabled" } else { "disabled" }, description);
+                }
+                return;
+            }
+            set_active_rules(registry);
+        }
+        Err(e) => {
+            eprintln!("Invalid rule selection: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let parquet_options = match parquet_options(&matches) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Invalid Parquet options: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match matches.value_of("date_order").unwrap().parse::<DateOrder>() {
+        Ok(order) => {
+            let formats: Vec<&str> = matches.values_of("date_format").map(|v| v.collect()).unwrap_or_default();
+            set_date_parser(DateParser::new(&formats, order));
+        }
+        Err(e) => {
+            eprintln!("Invalid --date-order: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(path) = matches.value_of("treatments") {
+        match TreatmentSet::load(path) {
+            Ok(treatments) => set_active_treatments(treatments),
+            Err(e) => {
+                eprintln!("Invalid treatments file {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Expectations are loaded up front so a bad file fails before any data is read
+    let expectations = if report == "VALIDATE" {
+        let path = matches.value_of("expectations").unwrap_or_else(|| {
+            eprintln!("--expectations is required for the VALIDATE report");
+            std::process::exit(2);
+        });
+        match Expectations::load(path) {
+            Ok(expectations) => Some(expectations),
+            Err(e) => {
+                eprintln!("Invalid expectations file {}: {}", path, e);
+                std::process::exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
     if report == "CP" {
         //character_profiling();
         match character_profiling() {
             Ok(_) => println!("--------END OF REPORT--------"),
             Err(e) => eprintln!("Error occurred during character profiling: {}", e),
         }
+    } else if report == "META" {
+        let parquet_path = match matches.value_of("parquet_path") {
+            Some(path) => path,
+            None => {
+                eprintln!("--parquet-path is required for the META report");
+                std::process::exit(1);
+            }
+        };
+        match parquet_metadata_report(parquet_path, &parquet_options, matches.value_of("grain").unwrap()) {
+            Ok(_) => println!("--------END OF REPORT--------"),
+            Err(e) => {
+                eprintln!("Error occurred during Parquet metadata report: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if report == "CPC" {
+        let expected = match ExpectedChars::parse(matches.value_of("expected_chars").unwrap()) {
+            Ok(expected) => expected,
+            Err(e) => {
+                eprintln!("Invalid --expected-chars: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let result = column_character_profiling(
+            matches.value_of("format").unwrap(),
+            matches.value_of("delimiter").unwrap().as_bytes()[0],
+            matches.value_of("header_row").unwrap().parse().expect("header-row must be a valid number"),
+            matches.value_of("pathdepth").unwrap().parse().expect("pathdepth must be a valid number"),
+            remove_array_numbers,
+            expected,
+            matches.value_of("maxlen").unwrap().parse().expect("maxlen must be a valid number"),
+        );
+        match result {
+            Ok(_) => println!("--------END OF REPORT--------"),
+            Err(e) => {
+                eprintln!("Error occurred during column character profiling: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if matches.value_of("format") == Some("excel")
+        && (matches.is_present("all_sheets") || matches.is_present("sheets"))
+    {
+        let excel_path = matches.value_of("excel_path")
+            .expect("--excel-path is required when format is 'excel'");
+        // An explicit --header-row applies to every sheet; otherwise each sheet's guess is used
+        let header_row = if matches.occurrences_of("header_row") > 0 {
+            Some(matches.value_of("header_row").unwrap().parse().expect("header-row must be a valid number"))
+        } else {
+            None
+        };
+        let result = excel_workbook_report(
+            excel_path,
+            matches.value_of("sheets"),
+            header_row,
+            &excel_options(&matches),
+            matches.value_of("grain").unwrap(),
+            matches.value_of("maxlen").unwrap().parse().expect("maxlen must be a valid number"),
+        );
+        if let Err(e) = result {
+            eprintln!("Error occurred during workbook profiling: {}", e);
+            std::process::exit(1);
+        }
     } else {
-        let grain = matches.value_of("grain").unwrap();
+        // Expectations are written against masks of one grain
+        let grain = expectations
+            .as_ref()
+            .and_then(|e| e.grain.as_deref())
+            .unwrap_or_else(|| matches.value_of("grain").unwrap());
         let delimiter_str = matches.value_of("delimiter").unwrap();
         let delimiter = delimiter_str.as_bytes()[0]; // Convert first character to u8
         let format = matches.value_of("format").unwrap();
@@ -836,6 +2193,22 @@ fn main() {
         let column_names: Arc<Mutex<HashMap<String, usize>>> = Arc::new(Mutex::new(HashMap::new()));
         let field_count_map: Arc<Mutex<HashMap<usize, usize>>> = Arc::new(Mutex::new(HashMap::new()));
         let record_count: Arc<Mutex<usize>> = Arc::new(Mutex::new(0));
+        // Assertion results per column, collected only with --scorecard
+        let scorecard_enabled = matches.is_present("scorecard")
+            || expectations.as_ref().is_some_and(|e| e.needs_scorecard());
+        let scorecard: Arc<Mutex<Scorecard>> = Arc::new(Mutex::new(Scorecard::new()));
+        // Null and length counts per column, for learning and checking expectations
+        let stats_enabled = report == "VALIDATE" || report == "LEARN";
+        let value_stats: Arc<Mutex<RecordStats>> = Arc::new(Mutex::new(RecordStats::new()));
+        // Date formats per column, collected only with --dates
+        let dates_enabled = matches.is_present("dates");
+        let date_inference: Arc<Mutex<HashMap<String, DateInference>>> = Arc::new(Mutex::new(HashMap::new()));
+        // Postal code matches per post*/zip* column, collected only with --postcodes
+        let postcodes_enabled = matches.is_present("postcodes");
+        let postcode_profiles: Arc<Mutex<HashMap<String, PostcodeProfile>>> = Arc::new(Mutex::new(HashMap::new()));
+        // Value shapes per column, collected with --types, --schema, LEARN and VALIDATE
+        let types_enabled = matches.is_present("types") || matches.is_present("schema") || stats_enabled;
+        let type_profiles: Arc<Mutex<HashMap<String, TypeProfile>>> = Arc::new(Mutex::new(HashMap::new()));
 
         let pathdepth = matches
             .value_of("pathdepth")
@@ -851,6 +2224,31 @@ fn main() {
 
         let extract_array_field = matches.value_of("extract_array");
 
+        let fixed_layout: Option<FixedWidthLayout> = if format == "fixed" {
+            let layout_spec = matches.value_of("layout")
+                .expect("--layout is required when format is 'fixed'");
+            match FixedWidthLayout::load(layout_spec) {
+                Ok(layout) => Some(layout),
+                Err(e) => {
+                    eprintln!("Invalid fixed-width layout: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            None
+        };
+
+        // Fixed-width records carry no header line, so data starts at the first line
+        let first_data_line = if fixed_layout.is_some() { 0 } else { header_row + 1 };
+
+        // Raw layout diagnostics, only available when reading text from stdin
+        let mut file_structure: Option<FileStructure> = None;
+        // Excel cell types, errors, formulas and layout, counted below the header row
+        let mut excel_diagnostics: Option<ExcelDiagnostics> = None;
+
+        // Excel rows, already split into cells
+        let mut excel_rows: Vec<Vec<String>> = Vec::new();
+
         // Handle Excel files differently
         let mut lines: Vec<String> = if format == "excel" {
             // Excel processing
@@ -864,25 +2262,103 @@ fn main() {
                 .expect("Sheet index must be a valid number");
 
             // Read Excel data
+            let options = excel_options(&matches);
             let rows = if let Some(sheet_name) = sheet_name_opt {
-                ExcelReader::read_sheet_by_name(excel_path, sheet_name)
+                ExcelReader::read_cells_by_name_with(excel_path, sheet_name, &options)
                     .expect("Failed to read Excel sheet by name")
             } else {
-                ExcelReader::read_sheet_by_index(excel_path, sheet_index)
+                ExcelReader::read_cells_by_index_with(excel_path, sheet_index, &options)
                     .expect("Failed to read Excel sheet by index")
             };
 
-            // Convert rows to delimited strings
-            rows.into_iter()
-                .map(|row| row.join(&(delimiter as char).to_string()))
-                .collect()
+            // Tables and named ranges may live on another sheet than --sheet selects
+            let layout = match (&options.region, sheet_name_opt) {
+                (Some(ExcelRegion::Table(_)), _) | (Some(ExcelRegion::NamedRange(_)), _) => None,
+                (_, Some(name)) => Some(name.to_string()),
+                (_, None) => ExcelReader::get_sheet_names(excel_path).ok().and_then(|names| names.get(sheet_index).cloned()),
+            }
+            .and_then(|name| ExcelReader::sheet_overview(excel_path, &name).ok());
+            excel_diagnostics = Some(ExcelDiagnostics::new(&rows, header_row, grain, layout));
+
+            // Keep rows as cell vectors; joining and re-splitting them would
+            // break cells that contain the delimiter or quotes
+            excel_rows = rows
+                .into_iter()
+                .map(|row| row.into_iter().map(|cell| cell.value).collect())
+                .collect();
+            Vec::new()
         } else if format == "parquet" {
             let parquet_path = matches.value_of("parquet_path")
                 .expect("--parquet-path is required when format is 'parquet'");
-            ParquetReader::read_as_json_lines(parquet_path)
-                .expect("Failed to read Parquet file")
+            let options = &parquet_options;
+            // Rules run on values as the JSON pipeline renders them
+            if enhanced_output || flat_enhanced || scorecard_enabled || stats_enabled || dates_enabled || postcodes_enabled || types_enabled || matches.is_present("cleanse") {
+                ParquetReader::read_as_json_lines_with(parquet_path, options)
+                    .expect("Failed to read Parquet file")
+            } else {
+                // Mask the Arrow columns directly rather than re-parsing a JSON line per row
+                let mask = |value: &str, path: &str| mask_value(value, grain, path);
+                let profile = ParquetReader::profile_columns(parquet_path, options, remove_array_numbers, pathdepth, &mask)
+                    .expect("Failed to read Parquet file");
+                *record_count.lock().unwrap() = profile.rows;
+
+                let mut local_column_names = column_names.lock().unwrap();
+                let mut local_frequency_maps = frequency_maps.lock().unwrap();
+                let mut local_example_maps = example_maps.lock().unwrap();
+                for (idx, column) in profile.into_columns().into_iter().enumerate() {
+                    local_column_names.insert(column.name, idx);
+                    local_frequency_maps.push(column.counts);
+                    local_example_maps.push(column.examples);
+                }
+                Vec::new()
+            }
+        } else if format == "avro" {
+            match matches.value_of("avro_path") {
+                Some(avro_path) => AvroReader::read_as_json_lines(avro_path),
+                None => AvroReader::read_from_reader(stdin.lock()),
+            }
+            .expect("Failed to read Avro input")
+        } else if format == "arrow" || format == "feather" {
+            match matches.value_of("arrow_path") {
+                Some(arrow_path) => ArrowIpcReader::read_as_json_lines(arrow_path),
+                None => ArrowIpcReader::read_from_reader(stdin.lock()),
+            }
+            .expect("Failed to read Arrow IPC input")
+        } else if format == "xml" {
+            let input = open_reader(stdin.lock()).expect("Failed to read from stdin");
+            XmlReader::read_as_json_lines(input, matches.value_of("xml_record"))
+                .expect("Failed to read XML input")
+        } else if let Some(files) = matches.values_of("files") {
+            let paths: Vec<&str> = files.collect();
+            let per_file: Vec<(Vec<String>, FileStructure)> = paths
+                .par_iter()
+                .map(|path| {
+                    let mut structure = FileStructure::new();
+                    let file_lines = open_path(path)
+                        .and_then(|reader| read_lines_with_structure(reader, &mut structure))
+                        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+                    Ok((file_lines, structure))
+                })
+                .collect::<io::Result<_>>()
+                .expect("Failed to read input file");
+
+            let mut structure = FileStructure::new();
+            let mut all_lines = Vec::new();
+            for (file_idx, (file_lines, file_structure)) in per_file.into_iter().enumerate() {
+                structure.merge(&file_structure);
+                // Each delimited file repeats its header, so keep only the first one
+                let skip = if file_idx > 0 && format == "tabular" { first_data_line } else { 0 };
+                all_lines.extend(file_lines.into_iter().skip(skip));
+            }
+            file_structure = Some(structure);
+            all_lines
         } else {
-            stdin.lock().lines().filter_map(Result::ok).collect()
+            let mut structure = FileStructure::new();
+            let input = open_reader(stdin.lock()).expect("Failed to read from stdin");
+            let stdin_lines = read_lines_with_structure(input, &mut structure)
+                .expect("Failed to read from stdin");
+            file_structure = Some(structure);
+            stdin_lines
         };
 
         // Handle array extraction for JSON format
@@ -903,15 +2379,45 @@ fn main() {
             }
         }
 
+        // Fixed-width column names come from the layout
+        if let Some(layout) = &fixed_layout {
+            let mut local_column_names = column_names.lock().unwrap();
+            let mut local_frequency_maps = frequency_maps.lock().unwrap();
+            let mut local_example_maps = example_maps.lock().unwrap();
+            for (idx, name) in layout.column_names().into_iter().enumerate() {
+                local_column_names.insert(name, idx);
+                local_frequency_maps.push(HashMap::new());
+                local_example_maps.push(HashMap::new());
+            }
+        }
+
+        if matches.is_present("cleanse") {
+            let cleanser = match matches.value_of("cleanse_map") {
+                Some(path) => Cleanser::load(path).unwrap_or_else(|e| {
+                    eprintln!("Invalid cleanse mapping {}: {}", path, e);
+                    std::process::exit(1);
+                }),
+                None => Cleanser::append_all(),
+            };
+            if let Err(e) = cleanse_output(&cleanser, &lines, excel_rows, format, delimiter, header_row, fixed_layout.as_ref()) {
+                eprintln!("Error occurred during cleansing: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+
         // For tabular/Excel data, process the header first (sequentially)
         if format == "tabular" || format == "excel" {
-            if lines.len() > header_row {
-                let header_line = &lines[header_row];
+            let headers = if format == "excel" {
+                excel_rows.get(header_row).cloned()
+            } else {
+                lines.get(header_row).map(|header_line| parse_csv_line(header_line, delimiter))
+            };
+            if let Some(headers) = headers {
                 let mut local_column_names = column_names.lock().unwrap();
                 let mut local_frequency_maps = frequency_maps.lock().unwrap();
                 let mut local_example_maps = example_maps.lock().unwrap();
 
-                let headers = parse_csv_line(header_line, delimiter);
                 for (idx, name) in headers
                     .iter()
                     .map(|s| s.trim().replace(" ", "_"))
@@ -924,13 +2430,167 @@ fn main() {
             }
         }
 
+        // Read ambiguous dates in enhanced output and the scorecard the way
+        // the rest of their column is written, naming columns as the rules do
+        if enhanced_output || flat_enhanced || scorecard_enabled {
+            if matches!(format, "json" | "parquet" | "xml" | "avro" | "arrow" | "feather") {
+                set_column_date_formats(lines.iter().filter_map(|line| serde_json::from_str::<Value>(line).ok()).flat_map(|record| {
+                    let mut fields = Vec::new();
+                    if enhanced_output || flat_enhanced {
+                        json_rule_fields(&record, "", &mut fields);
+                    } else {
+                        collect_json_fields(&record, String::new(), remove_array_numbers, pathdepth, 0, &mut fields);
+                    }
+                    fields
+                }));
+            } else {
+                let local_column_names = column_names.lock().unwrap();
+                let mut names: Vec<(&String, &usize)> = local_column_names.iter().collect();
+                names.sort_unstable_by_key(|(_, idx)| **idx);
+                let names: Vec<String> = names.into_iter().map(|(name, _)| name.clone()).collect();
+                let rows = lines
+                    .iter()
+                    .skip(first_data_line)
+                    .filter(|line| !line.is_empty())
+                    .map(|line| match &fixed_layout {
+                        Some(layout) => layout.slice(line),
+                        None => parse_csv_line(line, delimiter),
+                    })
+                    .chain(excel_rows.iter().skip(first_data_line).cloned());
+                set_column_date_formats(rows.flat_map(|fields| names.clone().into_iter().zip(fields)));
+            }
+        }
+
+        // Tabular records arrive as field vectors: text lines are split by the
+        // fixed-width layout or the CSV parser, Excel rows are already split
+        // into cells so their contents are never re-tokenised
+        let process_tabular_fields = |line_idx: usize, fields: Vec<String>| {
+            // Skip lines up to and including the header row, then process data
+            if line_idx >= first_data_line {
+                let mut local_column_names = column_names.lock().unwrap();
+                let mut local_record_count = record_count.lock().unwrap();
+                let mut local_frequency_maps = frequency_maps.lock().unwrap();
+                let mut local_example_maps = example_maps.lock().unwrap();
+                // Process tabular data
+                if !local_column_names.is_empty() {
+                    let mut processed_fields = Vec::new();
+
+                    for (i, field) in fields.iter().enumerate() {
+                        let column_name = match local_column_names.iter().find(|(_, &v)| v == i) {
+                            Some((name, _)) => name.clone(),
+                            None => {
+                                // Handle ragged data - this field has no corresponding column
+                                let extra_column_index = if i + 1 > local_column_names.len() {
+                                    i + 1 - local_column_names.len()
+                                } else {
+                                    // This shouldn't happen but handle gracefully
+                                    0
+                                };
+                                let new_name = format!("RaggedErr{}", extra_column_index);
+
+                                // Update column_names, frequency_maps, and example_maps for the new column
+                                //local_column_names.insert(new_name.clone(), local_column_names.len());
+                                let current_length = local_column_names.len();
+                                local_column_names.insert(new_name.clone(), current_length);
+
+                                local_frequency_maps.push(HashMap::new());
+                                local_example_maps.push(HashMap::new());
+
+                                new_name
+                            }
+                        };
+                        processed_fields.push((column_name, field.as_str()));
+                    }
+
+                    if scorecard_enabled {
+                        for (name, value) in &processed_fields {
+                            score_value(&scorecard, name, value);
+                        }
+                    }
+                    if stats_enabled {
+                        value_stats
+                            .lock()
+                            .unwrap()
+                            .observe(processed_fields.iter().map(|(name, value)| (name.as_str(), *value)));
+                    }
+                    if dates_enabled {
+                        for (name, value) in &processed_fields {
+                            observe_date(&date_inference, name, value);
+                        }
+                    }
+                    if postcodes_enabled {
+                        for (name, value) in &processed_fields {
+                            observe_postcode(&postcode_profiles, name, value);
+                        }
+                    }
+                    if types_enabled {
+                        for (name, value) in &processed_fields {
+                            observe_type(&type_profiles, name, value);
+                        }
+                    }
+
+                    let field_count = processed_fields.len();
+                    let mut field_count_map_guard = field_count_map.lock().unwrap();
+                    *field_count_map_guard.entry(field_count).or_insert(0) += 1;
+
+                    for (name, value) in &processed_fields {
+                        let masked_value = mask_value(value, grain, name);
+
+                        if let Some(idx) = local_column_names.get(name) {
+                            let count = local_frequency_maps[*idx]
+                                .entry(masked_value.clone())
+                                .or_insert(0);
+                            *count += 1;
+
+                            // Reservoir sampling
+                            let mut rng = thread_rng();
+                            if rng.gen::<f64>() < 1.0 / (*count as f64) {
+                                local_example_maps[*idx]
+                                    .insert(masked_value.clone(), value.to_string());
+                            }
+                        } else {
+                            // Handle the case when the column name is not found in the HashMap
+                            println!(
+                                "Warning: Column name not found in the HashMap: {}",
+                                name
+                            );
+                        }
+                    }
+
+                    // collect tabular data to enhance, enhance, print
+                    if enhanced_output {
+                        let processed_fields: Vec<(String, String)> = local_column_names.iter().map(|column_name| {
+                            let value = fields.get(*column_name.1).cloned().unwrap_or_default();
+                            (column_name.0.clone(), value)
+                        }).collect();
+
+                        let json_line = process_tabular_line_as_json(&processed_fields);
+                        //let enhanced_json_line = process_data(&json_line);
+                        println!("{}", serde_json::to_string(&json_line).unwrap());
+                    } else if flat_enhanced {
+                        let processed_fields: Vec<(String, String)> = local_column_names.iter().map(|column_name| {
+                            let value = fields.get(*column_name.1).cloned().unwrap_or_default();
+                            (column_name.0.clone(), value)
+                        }).collect();
+
+                        let json_line = process_tabular_line_as_json(&processed_fields);
+                        match flatten_json_object::Flattener::new().flatten(&json_line) {
+                            Ok(flattened) => println!("{}", serde_json::to_string(&flattened).unwrap()),
+                            Err(e) => eprintln!("Failed to flatten tabular JSON: {}", e),
+                        }
+                    }
+                }
+                *local_record_count += 1;
+            }
+        };
+
         // Now we move the loop into a parallel iterator
         lines.par_iter().enumerate().for_each(|(line_idx, line)| {
             if !line.is_empty() {
                 // Excel and tabular share the same processing logic
                 let actual_format = match format {
-                    "excel" => "tabular",
-                    "parquet" => "json",
+                    "excel" | "fixed" => "tabular",
+                    "parquet" | "xml" | "avro" | "arrow" | "feather" => "json",
                     _ => format,
                 };
 
@@ -959,128 +2619,153 @@ fn main() {
                             pathdepth,
                             remove_array_numbers,
                         );
-                    }
-                } else {
-                    // Tabular processing
-                    // Skip lines up to and including the header row, then process data
-                    if line_idx > header_row {
-                        let mut local_column_names = column_names.lock().unwrap();
-                        let mut local_record_count = record_count.lock().unwrap();
-                        let mut local_frequency_maps = frequency_maps.lock().unwrap();
-                        let mut local_example_maps = example_maps.lock().unwrap();
-                        // Process tabular data
-                        if !local_column_names.is_empty() {
-                            let fields = parse_csv_line(line, delimiter);
-                            let mut processed_fields = Vec::new();
-
-                            for (i, field) in fields.iter().enumerate() {
-                                let column_name = match local_column_names.iter().find(|(_, &v)| v == i) {
-                                    Some((name, _)) => name.clone(),
-                                    None => {
-                                        // Handle ragged data - this field has no corresponding column
-                                        let extra_column_index = if i + 1 > local_column_names.len() {
-                                            i + 1 - local_column_names.len()
-                                        } else {
-                                            // This shouldn't happen but handle gracefully
-                                            0
-                                        };
-                                        let new_name = format!("RaggedErr{}", extra_column_index);
-
-                                        // Update column_names, frequency_maps, and example_maps for the new column
-                                        //local_column_names.insert(new_name.clone(), local_column_names.len());
-                                        let current_length = local_column_names.len();
-                                        local_column_names.insert(new_name.clone(), current_length);
-
-                                        local_frequency_maps.push(HashMap::new());
-                                        local_example_maps.push(HashMap::new());
-
-                                        new_name
+                        drop(local_frequency_maps);
+                        drop(local_example_maps);
+                        drop(local_column_names);
+                        *record_count.lock().unwrap() += 1;
+
+                        if scorecard_enabled || stats_enabled || dates_enabled || postcodes_enabled || types_enabled {
+                            if let Ok(json_value) = serde_json::from_str::<Value>(line) {
+                                let mut fields = Vec::new();
+                                collect_json_fields(&json_value, String::new(), remove_array_numbers, pathdepth, 0, &mut fields);
+                                if scorecard_enabled {
+                                    for (path, value) in &fields {
+                                        score_value(&scorecard, path, value);
                                     }
-                                };
-                                processed_fields.push((column_name, field.as_str()));
-                            }
-
-                            let field_count = processed_fields.len();
-                            let mut field_count_map_guard = field_count_map.lock().unwrap();
-                            *field_count_map_guard.entry(field_count).or_insert(0) += 1;
-
-                            for (name, value) in &processed_fields {
-                                let masked_value = mask_value(value, grain, &name);
-
-                                if let Some(idx) = local_column_names.get(name) {
-                                    let count = local_frequency_maps[*idx]
-                                        .entry(masked_value.clone())
-                                        .or_insert(0);
-                                    *count += 1;
-
-                                    // Reservoir sampling
-                                    let mut rng = thread_rng();
-                                    if rng.gen::<f64>() < 1.0 / (*count as f64) {
-                                        local_example_maps[*idx]
-                                            .insert(masked_value.clone(), value.to_string());
+                                }
+                                if stats_enabled {
+                                    value_stats
+                                        .lock()
+                                        .unwrap()
+                                        .observe(fields.iter().map(|(path, value)| (path.as_str(), value.as_str())));
+                                }
+                                if dates_enabled {
+                                    for (path, value) in &fields {
+                                        observe_date(&date_inference, path, value);
                                     }
-                                } else {
-                                    // Handle the case when the column name is not found in the HashMap
-                                    println!(
-                                        "Warning: Column name not found in the HashMap: {}",
-                                        name
-                                    );
                                 }
-                            }
-
-                            // collect tabular data to enhance, enhance, print
-                            if enhanced_output {
-                                let processed_fields: Vec<(String, String)> = local_column_names.iter().map(|column_name| {
-                                    let value = fields[*column_name.1].clone();
-                                    (column_name.0.clone(), value)
-                                }).collect();
-
-                                let json_line = process_tabular_line_as_json(&processed_fields);
-                                //let enhanced_json_line = process_data(&json_line);
-                                println!("{}", serde_json::to_string(&json_line).unwrap());
-                            } else if flat_enhanced {
-                                let processed_fields: Vec<(String, String)> = local_column_names.iter().map(|column_name| {
-                                    let value = fields[*column_name.1].clone();
-                                    (column_name.0.clone(), value)
-                                }).collect();
-
-                                let json_line = process_tabular_line_as_json(&processed_fields);
-                                match flatten_json_object::Flattener::new().flatten(&json_line) {
-                                    Ok(flattened) => println!("{}", serde_json::to_string(&flattened).unwrap()),
-                                    Err(e) => eprintln!("Failed to flatten tabular JSON: {}", e),
+                                if postcodes_enabled {
+                                    for (path, value) in &fields {
+                                        observe_postcode(&postcode_profiles, path, value);
+                                    }
+                                }
+                                if types_enabled {
+                                    for (path, value) in &fields {
+                                        observe_type(&type_profiles, path, value);
+                                    }
                                 }
                             }
                         }
-                        *local_record_count += 1;
                     }
+                } else {
+                    // Tabular processing
+                    let fields = match &fixed_layout {
+                        Some(layout) => layout.slice(line),
+                        None => parse_csv_line(line, delimiter),
+                    };
+                    process_tabular_fields(line_idx, fields);
                 }
                 
             }
         });
+        excel_rows
+            .into_par_iter()
+            .enumerate()
+            .for_each(|(row_idx, row)| process_tabular_fields(row_idx, row));
 
         // Output the processed tabular line in JSON format if the enhanced_output flag is set to true
 
-        if enhanced_output == false {
+        if enhanced_output || flat_enhanced {
+            with_active_treatments(|treatments| treatments.map(print_treatment_summary));
+        }
+
+        if stats_enabled {
+            let column_names_ref = column_names.lock().unwrap();
+            let frequency_maps_ref = frequency_maps.lock().unwrap();
+            let value_stats_ref = value_stats.lock().unwrap();
+            let type_profiles_ref = type_profiles.lock().unwrap();
+            let mut columns: Vec<(&String, &usize)> = column_names_ref.iter().collect();
+            columns.sort_unstable_by_key(|(_, idx)| **idx);
+
+            // Ragged rows are those whose field count differs from the header
+            let header_width = columns.iter().filter(|(name, _)| !name.starts_with("RaggedErr")).count();
+            let ragged_rows = field_count_map
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(field_count, _)| **field_count != header_width)
+                .map(|(_, rows)| *rows)
+                .sum();
+
+            let profile = Profile {
+                rows: value_stats_ref.rows,
+                ragged_rows,
+                columns: columns
+                    .iter()
+                    .map(|(name, idx)| ColumnProfile {
+                        name: name.to_string(),
+                        masks: frequency_maps_ref.get(**idx).cloned().unwrap_or_default(),
+                        stats: value_stats_ref.column(name),
+                        inferred: type_profiles_ref
+                            .get(*name)
+                            .and_then(|types| with_date_parser(|parser| types.inferred_type(parser))),
+                    })
+                    .collect(),
+            };
+
+            if let Some(expectations) = &expectations {
+                let validation = expectations.validate(&profile, Some(&scorecard.lock().unwrap()));
+                print_validation(&validation, matches.value_of("expectations").unwrap(), profile.rows);
+                if !validation.passed() {
+                    std::process::exit(1);
+                }
+            } else {
+                let min_mask_share: f64 = matches
+                    .value_of("min_mask_share")
+                    .unwrap()
+                    .parse()
+                    .expect("min-mask-share must be a number between 0 and 1");
+                let learned = Expectations::learn(&profile, grain, min_mask_share);
+                print!("{}", serde_yaml::to_string(&learned).expect("Failed to write expectations"));
+            }
+        } else if let Some(schema) = matches.value_of("schema") {
+            let column_names_ref = column_names.lock().unwrap();
+            let mut columns: Vec<(String, usize)> =
+                column_names_ref.iter().map(|(name, idx)| (name.clone(), *idx)).collect();
+            columns.sort_unstable_by_key(|(_, idx)| *idx);
+            let suggestions = type_suggestions(&type_profiles.lock().unwrap(), &columns);
+            if schema == "sql" {
+                print!("{}", sql_schema(matches.value_of("schema_table").unwrap(), &suggestions));
+            } else {
+                println!("{}", serde_json::to_string_pretty(&arrow_schema(&suggestions)).unwrap());
+            }
+        } else if enhanced_output == false {
             let now = Local::now();
             let now_string = now.format("%Y%m%d %H:%M:%S").to_string();
             println!();
             println!("Data Profiling Report: {}", now_string);
             let record_count_value = record_count.lock().unwrap();
             println!("Examined rows: {}", record_count_value);
-            println!();
-            println!("FieldsPerLine:");
-            // Print the field count map
-            let field_count_map_ref = field_count_map.lock().unwrap();
-            for (field_count, frequency) in &*field_count_map_ref {
-                println!("{} fields: {} rows", field_count, frequency);
+            print_fields_per_line(&*field_count_map.lock().unwrap());
+
+            if let Some(structure) = &file_structure {
+                println!();
+                println!("{}", structure);
             }
 
-            println!();
-            println!(
-                "{:<32}\t{:<8}\t{:<8}\t{:<32}",
-                "column", "count", "pattern", "example"
-            );
-            println!("{:-<32}\t{:-<8}\t{:-<8}\t{:-<32}", "", "", "", "");
+            if let Some(diagnostics) = &excel_diagnostics {
+                let column_names_ref = column_names.lock().unwrap();
+                let names: Vec<String> = (0..diagnostics.cell_types.len())
+                    .map(|idx| {
+                        column_names_ref
+                            .iter()
+                            .find(|(_, &v)| v == idx)
+                            .map(|(name, _)| name.clone())
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                diagnostics.print(&names, maxlen);
+            }
 
             // sort the reporting lines
             let column_names_ref = column_names.lock().unwrap();
@@ -1088,28 +2773,71 @@ fn main() {
 
             sorted_column_names.sort_unstable_by_key(|(_, idx)| **idx);
 
-            for (name, idx) in sorted_column_names { 
-                let frequency_maps_ref = frequency_maps.lock().unwrap();
-                if let Some(frequency_map) = frequency_maps_ref.get(*idx) {
-                    let mut column_counts = frequency_map
-                        .iter()
-                        .map(|(value, count)| (value, count))
-                        .collect::<Vec<(&String, &usize)>>();
-
-                    column_counts.sort_unstable_by(|a, b| b.1.cmp(a.1));
-
-                    for (value, count) in column_counts {
-                        let empty_string = "".to_string();
-                        let example_maps_ref = example_maps.lock().unwrap();
-                        let example = example_maps_ref[*idx].get(value).unwrap_or(&empty_string);
-                        let truncated_example = truncate_string(&example, maxlen); // adjust the maximum length as needed
-                        println!(
-                            "col_{:05}_{}\t{:<8}\t{:<8}\t{:<32}",
-                            idx, name, count, value, truncated_example
-                        );
-                    }
-                }
+            let frequency_maps_ref = frequency_maps.lock().unwrap();
+            let example_maps_ref = example_maps.lock().unwrap();
+            print_pattern_table(
+                sorted_column_names.into_iter().filter_map(|(name, idx)| {
+                    let counts = frequency_maps_ref.get(*idx)?;
+                    Some((name.as_str(), *idx, counts, example_maps_ref.get(*idx)?))
+                }),
+                maxlen,
+            );
+
+            let mut columns: Vec<(String, usize)> =
+                column_names_ref.iter().map(|(name, idx)| (name.clone(), *idx)).collect();
+            columns.sort_unstable_by_key(|(_, idx)| *idx);
+            if types_enabled {
+                print_types(&type_suggestions(&type_profiles.lock().unwrap(), &columns), &columns, maxlen);
+            }
+            if dates_enabled {
+                print_dates(&date_inference.lock().unwrap(), &columns);
+            }
+            if postcodes_enabled {
+                print_postcodes(&postcode_profiles.lock().unwrap(), &columns, maxlen);
+            }
+            if scorecard_enabled {
+                print_scorecard(&scorecard.lock().unwrap(), &columns, maxlen);
             }
         } //End not enhanced_output
     }
 } // end of main
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_rules_and_treatments_use_the_full_path() {
+        let treatments = TreatmentSet::parse_yaml(
+            r#"
+treatments:
+  - { name: billing_postcode, column: billing.postcode, mask: A9A9A, action: insert, text: " ", at: -3 }
+  - { name: line_trim, column: "lines[].sku", mask: " A9", action: trim }
+"#,
+        )
+        .unwrap();
+        set_active_treatments(treatments);
+
+        let record = r#"{"postcode": "SW1A1AA", "billing": {"postcode": "SW1A1AA"}, "lines": [{"sku": " AB12"}]}"#;
+        let enhanced = process_json_line_as_json(record, "LU");
+        assert!(enhanced["postcode"].get("Treatment").is_none());
+        assert_eq!(enhanced["billing"]["postcode"]["Treatment"]["value"], json!("SW1A 1AA"));
+        assert_eq!(enhanced["lines"][0]["sku"]["Treatment"]["value"], json!("AB12"));
+
+        // Two `date` keys under different parents are separate columns with
+        // their own date formats
+        let records = [
+            r#"{"order": {"date": "03/04/2015"}, "customer": {"date": "31/12/2015"}}"#,
+            r#"{"order": {"date": "12/31/2015"}, "customer": {"date": "03/04/2015"}}"#,
+        ];
+        set_column_date_formats(records.iter().flat_map(|record| {
+            let mut fields = Vec::new();
+            json_rule_fields(&serde_json::from_str(record).unwrap(), "", &mut fields);
+            fields
+        }));
+        let first = process_json_line_as_json(records[0], "LU");
+        let second = process_json_line_as_json(records[1], "LU");
+        assert_eq!(first["order"]["date"]["Rules"]["std_date"], json!("2015-03-04"));
+        assert_eq!(second["customer"]["date"]["Rules"]["std_date"], json!("2015-04-03"));
+    }
+}
@@ -0,0 +1,87 @@
+// The character masks behind every report: H and L map ASCII letters and
+// digits to `a`, `A` and `9`, HU and LU do the same for Unicode classes, and
+// the low-grain masks collapse runs of the same class.
+
+use unic::ucd::GeneralCategory as Category;
+
+pub fn identity_mask(value: &str) -> String {
+    value.to_string()
+}
+
+// this is a highgrain Mask that works for unicode data!
+pub fn high_grain_unicode_mask(c: char) -> char {
+    match c {
+        '0'..='9' => '9',
+        'a'..='z' => 'a',
+        'A'..='Z' => 'A',
+        c if c.is_ascii_punctuation() && (c == '"' || c == '-' || c == '.' || c == ',') => c,
+        c if c.is_whitespace() => ' ',
+        _ => {
+            let cat = Category::of(c);
+
+            match cat {
+                Category::UppercaseLetter => 'A',
+                Category::LowercaseLetter => 'a',
+                Category::TitlecaseLetter => 'A',
+                Category::OtherLetter => 'a',
+                Category::ModifierLetter => 'a',
+                Category::DecimalNumber => '9',
+                Category::LetterNumber => '9',
+                Category::OtherNumber => '9',
+                Category::SpaceSeparator => ' ',
+                Category::LineSeparator => ' ',
+                Category::ParagraphSeparator => ' ',
+                _ => '_',
+            }
+        }
+    }
+}
+
+pub fn high_grain_mask(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => 'a',
+            'A'..='Z' => 'A',
+            '0'..='9' => '9',
+            _ => c,
+        })
+        .collect()
+}
+
+pub fn low_grain_mask(value: &str) -> String {
+    let high_grain = high_grain_mask(value);
+    let mut output = String::new();
+    let mut last_char = None;
+
+    for c in high_grain.chars() {
+        if last_char != Some(c) {
+            output.push(c);
+            last_char = Some(c);
+        }
+    }
+    if output.is_empty() {
+        "_".to_string()
+    } else {
+        output
+    }
+}
+
+pub fn mask_value(value: &str, grain: &str, field_name: &str) -> String {
+    if field_name.contains(".Rules.") {
+        identity_mask(value)
+    } else {
+        match grain {
+            "H" => high_grain_mask(value),
+            "L" => low_grain_mask(value),
+            "HU" => value.chars().map(high_grain_unicode_mask).collect(),
+            "LU" => low_grain_mask(
+                &value
+                    .chars()
+                    .map(high_grain_unicode_mask)
+                    .collect::<String>(),
+            ),
+            _u => value.chars().map(high_grain_unicode_mask).collect(),
+        }
+    }
+}
@@ -1,15 +1,74 @@
+use crate::arrow_profile::ArrowProfile;
 #[cfg(feature = "parquet")]
-use arrow::array::*;
+use crate::arrow_json::record_batch_to_json_lines;
 #[cfg(feature = "parquet")]
-use arrow::datatypes::{DataType, TimeUnit};
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
 #[cfg(feature = "parquet")]
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
 #[cfg(feature = "parquet")]
-use serde_json::{Map, Value};
+use parquet::basic::ConvertedType;
+#[cfg(feature = "parquet")]
+use parquet::file::metadata::ParquetMetaData;
+#[cfg(feature = "parquet")]
+use parquet::file::statistics::Statistics;
+#[cfg(feature = "parquet")]
+use parquet::schema::types::SchemaDescriptor;
+#[cfg(feature = "parquet")]
+use rayon::prelude::*;
+#[cfg(feature = "parquet")]
+use std::cmp::Ordering;
 #[cfg(feature = "parquet")]
 use std::fs::File;
 use std::path::Path;
 
+/// Which part of a Parquet file to read
+#[derive(Debug, Clone, Default)]
+pub struct ParquetOptions {
+    /// Columns to read, by top-level name or dotted path (`user.address.city`,
+    /// `tags[]`). All columns are read when `None`.
+    pub columns: Option<Vec<String>>,
+    /// 0-based row group indices to read. All row groups are read when `None`.
+    pub row_groups: Option<Vec<usize>>,
+    /// Stop after this many rows, taken from the selected row groups in order
+    pub row_limit: Option<usize>,
+}
+
+/// File-level metadata of a Parquet file, restricted to the selected row
+/// groups and columns
+#[derive(Debug, Clone)]
+pub struct ParquetSummary {
+    pub rows: i64,
+    pub created_by: Option<String>,
+    pub row_groups: Vec<RowGroupSummary>,
+    pub columns: Vec<ColumnChunkSummary>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RowGroupSummary {
+    pub index: usize,
+    pub rows: i64,
+    pub compressed_bytes: i64,
+    pub uncompressed_bytes: i64,
+}
+
+/// Schema and built-in statistics of one leaf column, aggregated over the
+/// selected row groups
+#[derive(Debug, Clone)]
+pub struct ColumnChunkSummary {
+    /// Column path as bytefreq reports it with `-a`, e.g. `tags[]`
+    pub path: String,
+    /// Column path as stored in the Parquet schema, e.g. `tags.list.element`
+    pub parquet_path: String,
+    pub physical_type: String,
+    pub logical_type: String,
+    pub compression: String,
+    pub compressed_bytes: i64,
+    pub null_count: Option<u64>,
+    pub distinct_count: Option<u64>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
 #[cfg(feature = "parquet")]
 pub struct ParquetReader;
 
@@ -19,217 +78,360 @@ impl ParquetReader {
     /// This feeds into the existing JSON processing pipeline.
     pub fn read_as_json_lines<P: AsRef<Path>>(
         path: P,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Self::read_as_json_lines_with(path, &ParquetOptions::default())
+    }
+
+    /// Read the selected columns, row groups and rows of a Parquet file as JSON lines.
+    pub fn read_as_json_lines_with<P: AsRef<Path>>(
+        path: P,
+        options: &ParquetOptions,
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let file = File::open(&path)?;
         let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+        let mask = Self::projection(builder.parquet_schema(), options)?;
+        let row_groups = Self::selected_row_groups(builder.metadata(), options)?;
+
+        let mut builder = builder.with_projection(mask).with_row_groups(row_groups);
+        if let Some(limit) = options.row_limit {
+            builder = builder.with_limit(limit);
+        }
         let reader = builder.build()?;
 
         let mut json_lines = Vec::new();
 
         for batch_result in reader {
             let batch = batch_result?;
-            let num_rows = batch.num_rows();
-            let schema = batch.schema();
+            json_lines.extend(record_batch_to_json_lines(&batch)?);
+        }
 
-            for row_idx in 0..num_rows {
-                let mut row_map = Map::new();
+        Ok(json_lines)
+    }
 
-                for (col_idx, field) in schema.fields().iter().enumerate() {
-                    let column = batch.column(col_idx);
-                    let value = Self::array_value_to_json(column, row_idx);
-                    row_map.insert(field.name().clone(), value);
-                }
+    /// Profile a Parquet file column by column without converting rows to JSON.
+    /// Row groups are read and profiled in parallel, then merged.
+    /// `mask` receives each value rendered as a JSON literal and its dotted path.
+    pub fn profile_columns<P, F>(
+        path: P,
+        options: &ParquetOptions,
+        remove_array_numbers: bool,
+        pathdepth: usize,
+        mask: &F,
+    ) -> Result<ArrowProfile, Box<dyn std::error::Error>>
+    where
+        P: AsRef<Path>,
+        F: Fn(&str, &str) -> String + Sync,
+    {
+        let path = path.as_ref();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+        let projection = Self::projection(builder.parquet_schema(), options)?;
 
-                let json_str = serde_json::to_string(&Value::Object(row_map))?;
-                json_lines.push(json_str);
+        // A row limit is split over the row groups in order, so each group
+        // knows how many of its rows to read before they run in parallel
+        let mut remaining = options.row_limit.unwrap_or(usize::MAX);
+        let mut plan = Vec::new();
+        for row_group in Self::selected_row_groups(builder.metadata(), options)? {
+            if remaining == 0 {
+                break;
             }
+            let rows = builder.metadata().row_group(row_group).num_rows() as usize;
+            plan.push((row_group, remaining.min(rows)));
+            remaining = remaining.saturating_sub(rows);
         }
 
-        Ok(json_lines)
+        let profile = plan
+            .into_par_iter()
+            .map(|(row_group, limit)| -> Result<ArrowProfile, String> {
+                let mut profile = ArrowProfile::new(remove_array_numbers, pathdepth);
+                let reader = Self::open_row_group(path, &projection, row_group, limit)
+                    .map_err(|e| e.to_string())?;
+                for batch in reader {
+                    profile.add_batch(&batch.map_err(|e| e.to_string())?, mask);
+                }
+                Ok(profile)
+            })
+            .try_reduce(|| ArrowProfile::new(remove_array_numbers, pathdepth), |a, b| Ok(a.merge(b)))?;
+
+        Ok(profile)
     }
 
-    /// Convert a single value from an Arrow array at the given row index to a JSON Value.
-    fn array_value_to_json(array: &dyn Array, idx: usize) -> Value {
-        if array.is_null(idx) {
-            return Value::Null;
-        }
+    fn open_row_group(
+        path: &Path,
+        projection: &ProjectionMask,
+        row_group: usize,
+        limit: usize,
+    ) -> Result<ParquetRecordBatchReader, Box<dyn std::error::Error>> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+        Ok(builder
+            .with_projection(projection.clone())
+            .with_row_groups(vec![row_group])
+            .with_limit(limit)
+            .build()?)
+    }
 
-        match array.data_type() {
-            // P0: Integer types
-            DataType::Int8 => {
-                let arr = array.as_any().downcast_ref::<Int8Array>().unwrap();
-                Value::Number(arr.value(idx).into())
-            }
-            DataType::Int16 => {
-                let arr = array.as_any().downcast_ref::<Int16Array>().unwrap();
-                Value::Number(arr.value(idx).into())
-            }
-            DataType::Int32 => {
-                let arr = array.as_any().downcast_ref::<Int32Array>().unwrap();
-                Value::Number(arr.value(idx).into())
-            }
-            DataType::Int64 => {
-                let arr = array.as_any().downcast_ref::<Int64Array>().unwrap();
-                Value::Number(arr.value(idx).into())
-            }
-            DataType::UInt8 => {
-                let arr = array.as_any().downcast_ref::<UInt8Array>().unwrap();
-                Value::Number(arr.value(idx).into())
-            }
-            DataType::UInt16 => {
-                let arr = array.as_any().downcast_ref::<UInt16Array>().unwrap();
-                Value::Number(arr.value(idx).into())
-            }
-            DataType::UInt32 => {
-                let arr = array.as_any().downcast_ref::<UInt32Array>().unwrap();
-                Value::Number(arr.value(idx).into())
-            }
-            DataType::UInt64 => {
-                let arr = array.as_any().downcast_ref::<UInt64Array>().unwrap();
-                // UInt64 values above i64::MAX cannot be represented in serde_json::Number
-                // so we fall back to string representation for those
-                let val = arr.value(idx);
-                if let Some(n) = serde_json::Number::from_f64(val as f64) {
-                    Value::Number(n)
-                } else {
-                    Value::String(val.to_string())
+    /// Schema, row group sizes and the footer statistics of the selected
+    /// columns and row groups.
+    pub fn describe<P: AsRef<Path>>(
+        path: P,
+        options: &ParquetOptions,
+    ) -> Result<ParquetSummary, Box<dyn std::error::Error>> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?;
+        let metadata = builder.metadata();
+        let schema = builder.parquet_schema();
+        let leaves = Self::selected_leaves(schema, options)?;
+        let row_groups = Self::selected_row_groups(metadata, options)?;
+
+        let mut columns: Vec<ColumnChunkSummary> = leaves
+            .iter()
+            .map(|&leaf| {
+                let descr = schema.column(leaf);
+                let logical_type = match (descr.logical_type(), descr.converted_type()) {
+                    (Some(logical), _) => format!("{:?}", logical),
+                    (None, ConvertedType::NONE) => "-".to_string(),
+                    (None, converted) => converted.to_string(),
+                };
+                ColumnChunkSummary {
+                    path: Self::bytefreq_path(descr.path().parts()),
+                    parquet_path: descr.path().string(),
+                    physical_type: descr.physical_type().to_string(),
+                    logical_type,
+                    compression: "-".to_string(),
+                    compressed_bytes: 0,
+                    null_count: Some(0),
+                    distinct_count: None,
+                    min: None,
+                    max: None,
                 }
-            }
+            })
+            .collect();
+
+        let mut summaries = Vec::new();
+        let mut mins: Vec<Option<StatValue>> = vec![None; leaves.len()];
+        let mut maxs: Vec<Option<StatValue>> = vec![None; leaves.len()];
 
-            // P0: Float types
-            DataType::Float32 => {
-                let arr = array.as_any().downcast_ref::<Float32Array>().unwrap();
-                let val = arr.value(idx) as f64;
-                match serde_json::Number::from_f64(val) {
-                    Some(n) => Value::Number(n),
-                    None => Value::Null, // NaN/Inf
+        for &row_group in &row_groups {
+            let group = metadata.row_group(row_group);
+            summaries.push(RowGroupSummary {
+                index: row_group,
+                rows: group.num_rows(),
+                compressed_bytes: group.compressed_size(),
+                uncompressed_bytes: group.total_byte_size(),
+            });
+
+            for (slot, &leaf) in leaves.iter().enumerate() {
+                let chunk = group.column(leaf);
+                let summary = &mut columns[slot];
+                summary.compression = chunk.compression().to_string();
+                summary.compressed_bytes += chunk.compressed_size();
+
+                let stats = chunk.statistics();
+                summary.null_count = match (summary.null_count, stats.and_then(|s| s.null_count_opt())) {
+                    (Some(total), Some(nulls)) => Some(total + nulls),
+                    _ => None,
+                };
+                // Distinct counts cannot be added up across row groups
+                summary.distinct_count = if row_groups.len() == 1 {
+                    stats.and_then(|s| s.distinct_count_opt())
+                } else {
+                    None
+                };
+                if let Some(stats) = stats {
+                    let (min, max) = StatValue::min_max(stats);
+                    StatValue::keep(&mut mins[slot], min, Ordering::Less);
+                    StatValue::keep(&mut maxs[slot], max, Ordering::Greater);
                 }
             }
-            DataType::Float64 => {
-                let arr = array.as_any().downcast_ref::<Float64Array>().unwrap();
-                let val = arr.value(idx);
-                match serde_json::Number::from_f64(val) {
-                    Some(n) => Value::Number(n),
-                    None => Value::Null, // NaN/Inf
+        }
+
+        for (slot, summary) in columns.iter_mut().enumerate() {
+            summary.min = mins[slot].as_ref().map(StatValue::render);
+            summary.max = maxs[slot].as_ref().map(StatValue::render);
+        }
+
+        Ok(ParquetSummary {
+            rows: summaries.iter().map(|g| g.rows).sum(),
+            created_by: metadata.file_metadata().created_by().map(|s| s.to_string()),
+            row_groups: summaries,
+            columns,
+        })
+    }
+
+    fn selected_row_groups(
+        metadata: &ParquetMetaData,
+        options: &ParquetOptions,
+    ) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+        let available = metadata.num_row_groups();
+        match &options.row_groups {
+            None => Ok((0..available).collect()),
+            Some(row_groups) => {
+                if let Some(bad) = row_groups.iter().find(|&&rg| rg >= available) {
+                    return Err(format!(
+                        "Row group {} does not exist (file has {} row groups)",
+                        bad, available
+                    )
+                    .into());
                 }
+                Ok(row_groups.clone())
             }
+        }
+    }
 
-            // P0: String types
-            DataType::Utf8 => {
-                let arr = array.as_any().downcast_ref::<StringArray>().unwrap();
-                Value::String(arr.value(idx).to_string())
-            }
-            // P1: LargeUtf8
-            DataType::LargeUtf8 => {
-                let arr = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
-                Value::String(arr.value(idx).to_string())
-            }
+    fn projection(
+        schema: &SchemaDescriptor,
+        options: &ParquetOptions,
+    ) -> Result<ProjectionMask, Box<dyn std::error::Error>> {
+        match options.columns {
+            None => Ok(ProjectionMask::all()),
+            Some(_) => Ok(ProjectionMask::leaves(schema, Self::selected_leaves(schema, options)?)),
+        }
+    }
 
-            // P0: Boolean
-            DataType::Boolean => {
-                let arr = array.as_any().downcast_ref::<BooleanArray>().unwrap();
-                Value::Bool(arr.value(idx))
-            }
+    /// Leaf column indices matching the requested names. A name selects every
+    /// leaf at or below it, so `user` selects `user.name` and `user.address.city`.
+    fn selected_leaves(
+        schema: &SchemaDescriptor,
+        options: &ParquetOptions,
+    ) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+        let leaf_paths: Vec<String> = schema
+            .columns()
+            .iter()
+            .map(|c| Self::bytefreq_path(c.path().parts()))
+            .collect();
 
-            // P0: Null type
-            DataType::Null => Value::Null,
-
-            // P1: Timestamp types -> ISO8601 strings
-            DataType::Timestamp(unit, tz) => {
-                let epoch_str = match unit {
-                    TimeUnit::Second => {
-                        let a = array.as_any().downcast_ref::<TimestampSecondArray>().unwrap();
-                        Self::timestamp_to_iso8601(a.value(idx), 1, tz.as_deref())
-                    }
-                    TimeUnit::Millisecond => {
-                        let a = array.as_any().downcast_ref::<TimestampMillisecondArray>().unwrap();
-                        Self::timestamp_to_iso8601(a.value(idx), 1_000, tz.as_deref())
-                    }
-                    TimeUnit::Microsecond => {
-                        let a = array.as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
-                        Self::timestamp_to_iso8601(a.value(idx), 1_000_000, tz.as_deref())
-                    }
-                    TimeUnit::Nanosecond => {
-                        let a = array.as_any().downcast_ref::<TimestampNanosecondArray>().unwrap();
-                        Self::timestamp_to_iso8601(a.value(idx), 1_000_000_000, tz.as_deref())
-                    }
-                };
-                Value::String(epoch_str)
+        let requested = match &options.columns {
+            None => return Ok((0..leaf_paths.len()).collect()),
+            Some(requested) => requested,
+        };
+
+        let mut leaves = Vec::new();
+        for name in requested {
+            let name = Self::collapse_indices(name.trim());
+            let matching: Vec<usize> = leaf_paths
+                .iter()
+                .enumerate()
+                .filter(|(_, path)| {
+                    *path == &name
+                        || path.starts_with(&format!("{}.", name))
+                        || path.starts_with(&format!("{}[", name))
+                })
+                .map(|(idx, _)| idx)
+                .collect();
+            if matching.is_empty() {
+                return Err(format!("Column '{}' not found in Parquet schema", name).into());
             }
+            leaves.extend(matching);
+        }
+        leaves.sort_unstable();
+        leaves.dedup();
+        Ok(leaves)
+    }
 
-            // P1: Date types
-            DataType::Date32 => {
-                let arr = array.as_any().downcast_ref::<Date32Array>().unwrap();
-                let days = arr.value(idx);
-                let date = chrono::NaiveDate::from_num_days_from_ce_opt(days + 719_163);
-                match date {
-                    Some(d) => Value::String(d.format("%Y-%m-%d").to_string()),
-                    None => Value::String(days.to_string()),
-                }
+    /// Turn a Parquet leaf path into bytefreq's collapsed path naming: the
+    /// `list`/`element` levels of a LIST and the `key_value` level of a MAP
+    /// become `[]`, matching the JSON rendering of lists and maps.
+    fn bytefreq_path(parts: &[String]) -> String {
+        let mut path = String::new();
+        let mut i = 0;
+        while i < parts.len() {
+            let part = &parts[i];
+            let next = parts.get(i + 1).map(|s| s.as_str());
+            if part == "list" && matches!(next, Some("element") | Some("item")) && !path.is_empty() {
+                path.push_str("[]");
+                i += 2;
+                continue;
             }
-            DataType::Date64 => {
-                let arr = array.as_any().downcast_ref::<Date64Array>().unwrap();
-                let millis = arr.value(idx);
-                let secs = millis / 1000;
-                let nsecs = ((millis % 1000) * 1_000_000) as u32;
-                let dt = chrono::DateTime::from_timestamp(secs, nsecs);
-                match dt {
-                    Some(d) => Value::String(d.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
-                    None => Value::String(millis.to_string()),
-                }
+            if part == "key_value" && !path.is_empty() {
+                path.push_str("[]");
+                i += 1;
+                continue;
             }
-
-            // P0: Struct -> nested JSON object
-            DataType::Struct(fields) => {
-                let arr = array.as_any().downcast_ref::<StructArray>().unwrap();
-                let mut obj = Map::new();
-                for (i, field) in fields.iter().enumerate() {
-                    let child = arr.column(i);
-                    obj.insert(field.name().clone(), Self::array_value_to_json(child.as_ref(), idx));
-                }
-                Value::Object(obj)
+            if !path.is_empty() {
+                path.push('.');
             }
+            path.push_str(part);
+            i += 1;
+        }
+        path
+    }
 
-            // P0: List -> JSON array
-            DataType::List(_) => {
-                let arr = array.as_any().downcast_ref::<ListArray>().unwrap();
-                let values = arr.value(idx);
-                let mut items = Vec::new();
-                for i in 0..values.len() {
-                    items.push(Self::array_value_to_json(values.as_ref(), i));
+    /// `tags[3].name` -> `tags[].name`
+    fn collapse_indices(name: &str) -> String {
+        let mut collapsed = String::with_capacity(name.len());
+        let mut in_index = false;
+        for c in name.chars() {
+            match c {
+                '[' => {
+                    in_index = true;
+                    collapsed.push(c);
+                }
+                ']' => {
+                    in_index = false;
+                    collapsed.push(c);
                 }
-                Value::Array(items)
+                _ if in_index => {}
+                _ => collapsed.push(c),
             }
+        }
+        collapsed
+    }
+}
 
-            // P1: LargeList -> JSON array
-            DataType::LargeList(_) => {
-                let arr = array.as_any().downcast_ref::<LargeListArray>().unwrap();
-                let values = arr.value(idx);
-                let mut items = Vec::new();
-                for i in 0..values.len() {
-                    items.push(Self::array_value_to_json(values.as_ref(), i));
-                }
-                Value::Array(items)
+/// A typed min/max statistic, compared in its own type across row groups
+#[cfg(feature = "parquet")]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum StatValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+#[cfg(feature = "parquet")]
+impl StatValue {
+    fn min_max(stats: &Statistics) -> (Option<StatValue>, Option<StatValue>) {
+        fn bytes(value: &[u8]) -> StatValue {
+            match std::str::from_utf8(value) {
+                Ok(text) => StatValue::Text(text.to_string()),
+                Err(_) => StatValue::Bytes(value.to_vec()),
             }
+        }
+        match stats {
+            Statistics::Boolean(s) => (s.min_opt().map(|v| StatValue::Bool(*v)), s.max_opt().map(|v| StatValue::Bool(*v))),
+            Statistics::Int32(s) => (s.min_opt().map(|v| StatValue::Int(*v as i64)), s.max_opt().map(|v| StatValue::Int(*v as i64))),
+            Statistics::Int64(s) => (s.min_opt().map(|v| StatValue::Int(*v)), s.max_opt().map(|v| StatValue::Int(*v))),
+            Statistics::Float(s) => (s.min_opt().map(|v| StatValue::Float(*v as f64)), s.max_opt().map(|v| StatValue::Float(*v as f64))),
+            Statistics::Double(s) => (s.min_opt().map(|v| StatValue::Float(*v)), s.max_opt().map(|v| StatValue::Float(*v))),
+            Statistics::ByteArray(s) => (s.min_opt().map(|v| bytes(v.data())), s.max_opt().map(|v| bytes(v.data()))),
+            Statistics::FixedLenByteArray(s) => (
+                s.min_opt().map(|v| StatValue::Bytes(v.data().to_vec())),
+                s.max_opt().map(|v| StatValue::Bytes(v.data().to_vec())),
+            ),
+            // INT96 timestamps are deprecated and carry no usable ordering
+            Statistics::Int96(_) => (None, None),
+        }
+    }
 
-            // Unsupported types - never panic
-            other => Value::String(format!("<unsupported: {}>", other)),
+    /// Replace `current` with `candidate` if it compares as `wanted`
+    fn keep(current: &mut Option<StatValue>, candidate: Option<StatValue>, wanted: Ordering) {
+        if let Some(candidate) = candidate {
+            let replace = match current {
+                None => true,
+                Some(existing) => candidate.partial_cmp(existing) == Some(wanted),
+            };
+            if replace {
+                *current = Some(candidate);
+            }
         }
     }
 
-    /// Convert a timestamp value to an ISO8601 string.
-    /// `divisor` converts the raw value to seconds (1 for seconds, 1000 for millis, etc.)
-    fn timestamp_to_iso8601(raw: i64, divisor: i64, _tz: Option<&str>) -> String {
-        let secs = raw / divisor;
-        let remainder = (raw % divisor).unsigned_abs();
-        let nanos = if divisor == 1 {
-            0u32
-        } else {
-            (remainder * 1_000_000_000 / divisor as u64) as u32
-        };
-        match chrono::DateTime::from_timestamp(secs, nanos) {
-            Some(dt) => dt.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string(),
-            None => raw.to_string(),
+    fn render(&self) -> String {
+        match self {
+            StatValue::Bool(v) => v.to_string(),
+            StatValue::Int(v) => v.to_string(),
+            StatValue::Float(v) => v.to_string(),
+            StatValue::Text(v) => v.clone(),
+            StatValue::Bytes(v) => v.iter().map(|b| format!("{:02x}", b)).collect(),
         }
     }
 }
@@ -248,6 +450,34 @@ impl ParquetReader {
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         Err("Parquet support not enabled. Rebuild with --features parquet".into())
     }
+
+    pub fn read_as_json_lines_with<P: AsRef<Path>>(
+        _path: P,
+        _options: &ParquetOptions,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Err("Parquet support not enabled. Rebuild with --features parquet".into())
+    }
+
+    pub fn profile_columns<P, F>(
+        _path: P,
+        _options: &ParquetOptions,
+        _remove_array_numbers: bool,
+        _pathdepth: usize,
+        _mask: &F,
+    ) -> Result<ArrowProfile, Box<dyn std::error::Error>>
+    where
+        P: AsRef<Path>,
+        F: Fn(&str, &str) -> String + Sync,
+    {
+        Err("Parquet support not enabled. Rebuild with --features parquet".into())
+    }
+
+    pub fn describe<P: AsRef<Path>>(
+        _path: P,
+        _options: &ParquetOptions,
+    ) -> Result<ParquetSummary, Box<dyn std::error::Error>> {
+        Err("Parquet support not enabled. Rebuild with --features parquet".into())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -266,4 +496,13 @@ mod tests {
         let result = ParquetReader::read_as_json_lines("/nonexistent/file.parquet");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_bytefreq_paths() {
+        let parts = |p: &str| p.split('.').map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(ParquetReader::bytefreq_path(&parts("tags.list.element")), "tags[]");
+        assert_eq!(ParquetReader::bytefreq_path(&parts("user.address.city")), "user.address.city");
+        assert_eq!(ParquetReader::bytefreq_path(&parts("attrs.key_value.key")), "attrs[].key");
+        assert_eq!(ParquetReader::collapse_indices("tags[3].name"), "tags[].name");
+    }
 }
@@ -1,8 +1,10 @@
-use regex::Regex;
 use serde_json::json;
 use chrono::{NaiveDate, Utc};
 use geonamescache::mappers::country;
 use crate::cache::{COUNTRY_NAME_TO_ISO3_CACHE};
+use crate::rules::dates::{is_date_shaped, with_date_parser};
+use crate::rules::postcodes::{is_postcode_field, is_postcode_for, matching_countries};
+use crate::rules::registry::{FieldValue, Rule, RuleRegistry};
 
 // this is a library of assertion rules, that are matched to triples arriving (raw, HU, LU)
 
@@ -35,71 +37,20 @@ fn country_name_to_iso3(value: &str) -> Option<String> {
     result
 }
 
-fn get_possible_countries(_column_name: &str, raw: &str, hu: &str, lu: &str) -> Vec<String> {
-    let mut possible_countries: Vec<String> = Vec::new();
-
-    match hu {
-        "9999" => {
-            possible_countries.extend(vec!["AT", "BE", "BG", "CH", "CY", "CZ", "DK", "EE", "FI", "GR", "HU", "IE", "LT", "LU", "LV", "MT", "NL", "NO", "PL", "PT", "RO", "SE", "SI", "SK"].into_iter().map(|s| s.to_string()));
-        }
-        "99999" => {
-            possible_countries.extend(vec!["DE", "ES", "FR", "HR", "IT"].into_iter().map(|s| s.to_string()));
-        }
-        "999-99" => {
-            possible_countries.extend(vec!["SE"].into_iter().map(|s| s.to_string()));
-        }
-        "AAA-9999" => {
-            possible_countries.extend(vec!["IE"].into_iter().map(|s| s.to_string()));
-        }
-        _ => {}
-    }
-
-    // Additional country-specific checks
-    if lu == "9-9999" && raw.starts_with("1") {
-        possible_countries.retain(|country| country == "DE");
-    } else if hu == "9999" && raw.starts_with("0") {
-        possible_countries.retain(|country| country == "NL");
-    } else if hu == "99999" && raw.starts_with("9") {
-        possible_countries.retain(|country| country == "FR");
-    }
-
-    // UK postal code patterns
-    let uk_patterns = vec!["A9 9A", "A9A 9A", "A9A"];
-    if uk_patterns.contains(&lu) {
-        possible_countries.push("UK".to_string());
-    }
-
-    possible_countries
-}
-
-
 fn string_length(value: &str) -> i32 {
     let char_count = value.chars().count();
     return char_count as i32; 
 }
 
 
-fn parse_date(value: &str) -> Option<NaiveDate> {
-    let formats = [
-        "%d-%b-%Y",   // 31-Dec-2015
-        "%d-%m-%Y",   // 31-12-2015
-        "%d/%m/%Y",   // 31/12/2015
-        "%Y-%m-%d",   // 2015-12-31 (ISO 8601)
-        "%m/%d/%Y",   // 12/31/2015 (US format)
-        "%Y%m%d",     // 20151231 (compact)
-    ];
-
-    for format in &formats {
-        if let Ok(parsed_date) = NaiveDate::parse_from_str(value, format) {
-            return Some(parsed_date);
-        }
-    }
-
-    None
+// Ambiguous day/month values follow the column's inferred format when there
+// is one, otherwise the configured order, see rules::dates
+fn parse_date(field_name: &str, value: &str) -> Option<NaiveDate> {
+    with_date_parser(|parser| parser.parse_in_column(field_name, value))
 }
 
-fn is_sensible_dob(value: &str) -> bool {
-    if let Some(parsed_date) = parse_date(value) {
+fn is_sensible_dob(field_name: &str, value: &str) -> bool {
+    if let Some(parsed_date) = parse_date(field_name, value) {
         let now = Utc::now().naive_utc().date();
         let min_dob = now - chrono::Duration::weeks(127 * 52);
 
@@ -157,8 +108,7 @@ fn parse_unix_timestamp(value: &str, lu: &str) -> Option<(String, String, String
 }
 
 pub fn is_uk_postcode(value: &str) -> bool {
-    let re = Regex::new(r"^(([A-Z][A-HJ-Y]?\d[A-Z\d]?|ASCN|STHL|TDCU|BBND|[BFS]IQQ|PCRN|TKCA) ?\d[A-Z]{2}|BFPO ?\d{1,4}|(KY\d|MSR|VG|AI)[ -]?\d{4}|[A-Z]{2} ?\d{2}|GE ?CX|GIR ?0A{2}|SAN ?TA1)$").unwrap();
-    re.is_match(value)
+    is_postcode_for("GB", value)
 }
 
 pub fn is_numeric(value: &str) -> bool {
@@ -179,72 +129,202 @@ pub fn poss_longitude(value: &str) -> bool {
     false
 }
 
+/// Built-in rules in the order they run
+pub fn builtin_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(StringLength),
+        Box::new(PostalCountry),
+        Box::new(CountryName),
+        Box::new(IsNumeric),
+        Box::new(UkPostcode),
+        Box::new(StdDate),
+        Box::new(SensibleDob),
+        Box::new(UnixTimestamp),
+    ]
+}
+
+/// Run the built-in rules over one value
 pub fn execute_assertions(field_name: &str, raw: &str, lu: &str, hu: &str) -> serde_json::Value {
-    let mut assertions: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+    RuleRegistry::with_builtin_rules().evaluate(field_name, raw, lu, hu)
+}
 
-    // Remove double quotes from the input strings
-    let raw = raw.trim_matches('"');
-    let lu = lu.trim_matches('"');
-    let hu = hu.trim_matches('"');
+struct StringLength;
 
-    assertions.insert("string_length".to_string(), json!(string_length(raw)));
+impl Rule for StringLength {
+    fn name(&self) -> &str {
+        "string_length"
+    }
 
-    // sniff potential postal country
-    let target_substring = "post"; 
-    if field_name.to_lowercase().contains(&target_substring.to_lowercase()) {
-        let possible_countries = get_possible_countries(field_name, raw, hu, lu);
-        if !possible_countries.is_empty() {
-            assertions.insert("poss_postal_country".to_string(), json!(possible_countries));
-        }
+    fn description(&self) -> &str {
+        "Length of every value in characters"
     }
 
-    // Check country name
-    if field_name.to_lowercase().contains("country") && !lu.chars().any(|c| c.is_numeric()) {
-        if let Some((iso3, region_code)) = country_name_to_iso3(raw).map(|iso3| (iso3.clone(), format!("{}-{}", iso3, raw)))
-            .or_else(|| handle_country_name_variations(raw)) {
-            assertions.insert("std_country_iso3".to_string(), json!(iso3));
-        assertions.insert("std_region_code".to_string(), json!(region_code));
-        }
+    fn applies(&self, _value: &FieldValue) -> bool {
+        true
     }
 
-    if lu == "9" || lu == "9.9" {
-        assertions.insert("is_numeric".to_string(), json!(is_numeric(raw)));
-        //assertions.insert("poss_latitude".to_string(), json!(poss_latitude(raw)));
-        //assertions.insert("poss_longitude".to_string(), json!(poss_longitude(raw)));
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        Some(json!({ "string_length": string_length(value.raw) }))
     }
+}
+
+struct PostalCountry;
 
-    // Add more assertion checks based on different LU/HU patterns here
+impl Rule for PostalCountry {
+    fn name(&self) -> &str {
+        "poss_postal_country"
+    }
 
-    if lu == "A9 9A" || hu == "A9A 9A" {
-        assertions.insert("is_uk_postcode".to_string(), json!(is_uk_postcode(raw)));
+    fn description(&self) -> &str {
+        "Countries whose postal code format fits values of post* and zip* fields, see rules::postcodes"
     }
 
-    // Check for date patterns - either common LU patterns or field name contains "date"
-    if lu == "9_9_9" || lu == "9-9-9" || lu == "9/9/9" || lu == "9-Aa-9" || field_name.to_lowercase().contains("date") {
-        if let Some(parsed_date) = parse_date(raw) {
-            assertions.insert("std_date".to_string(), json!(parsed_date.format("%Y-%m-%d").to_string()));
+    fn applies(&self, value: &FieldValue) -> bool {
+        is_postcode_field(value.field_name) && !value.raw.trim().is_empty()
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        let possible_countries = matching_countries(value.raw);
+        if possible_countries.is_empty() {
+            Some(json!({ "is_valid_postcode": false }))
+        } else {
+            Some(json!({ "poss_postal_country": possible_countries, "is_valid_postcode": true }))
         }
     }
+}
+
+struct CountryName;
+
+impl Rule for CountryName {
+    fn name(&self) -> &str {
+        "std_country"
+    }
+
+    fn description(&self) -> &str {
+        "ISO3 code and region code for country names in country* fields"
+    }
+
+    fn applies(&self, value: &FieldValue) -> bool {
+        value.field_name.to_lowercase().contains("country") && !value.lu.chars().any(|c| c.is_numeric())
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        let raw = value.raw;
+        country_name_to_iso3(raw)
+            .map(|iso3| (iso3.clone(), format!("{}-{}", iso3, raw)))
+            .or_else(|| handle_country_name_variations(raw))
+            .map(|(iso3, region_code)| json!({ "std_country_iso3": iso3, "std_region_code": region_code }))
+    }
+}
+
+struct IsNumeric;
+
+impl Rule for IsNumeric {
+    fn name(&self) -> &str {
+        "is_numeric"
+    }
 
-    // check DOB
-    if hu == "99_99_9999" && field_name.to_lowercase().contains("dob") {
-        assertions.insert("is_sensible_dob".to_string(), json!(is_sensible_dob(raw)));
+    fn description(&self) -> &str {
+        "Whether number-shaped values (LU 9 or 9.9) parse as numbers"
+    }
+
+    fn applies(&self, value: &FieldValue) -> bool {
+        value.lu == "9" || value.lu == "9.9"
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        Some(json!({ "is_numeric": is_numeric(value.raw) }))
+    }
+}
+
+struct UkPostcode;
+
+impl Rule for UkPostcode {
+    fn name(&self) -> &str {
+        "is_uk_postcode"
+    }
+
+    fn description(&self) -> &str {
+        "Whether UK postcode-shaped values are valid UK postcodes"
+    }
+
+    fn applies(&self, value: &FieldValue) -> bool {
+        matches!(value.lu, "A9 9A" | "A9A 9A" | "A9A9A" | "A9A")
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        Some(json!({ "is_uk_postcode": is_uk_postcode(value.raw) }))
+    }
+}
+
+struct StdDate;
+
+impl Rule for StdDate {
+    fn name(&self) -> &str {
+        "std_date"
+    }
+
+    fn description(&self) -> &str {
+        "ISO date for date-shaped values and date* fields"
+    }
+
+    // Either a date-shaped LU mask or the field name contains "date"
+    fn applies(&self, value: &FieldValue) -> bool {
+        is_date_shaped(value.lu) || value.field_name.to_lowercase().contains("date")
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        parse_date(value.field_name, value.raw).map(|parsed_date| json!({ "std_date": parsed_date.format("%Y-%m-%d").to_string() }))
+    }
+}
+
+struct SensibleDob;
+
+impl Rule for SensibleDob {
+    fn name(&self) -> &str {
+        "is_sensible_dob"
+    }
+
+    fn description(&self) -> &str {
+        "Whether dates of birth in dob* fields fall within the last 127 years"
+    }
+
+    fn applies(&self, value: &FieldValue) -> bool {
+        value.hu == "99_99_9999" && value.field_name.to_lowercase().contains("dob")
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        Some(json!({ "is_sensible_dob": is_sensible_dob(value.field_name, value.raw) }))
+    }
+}
+
+struct UnixTimestamp;
+
+impl Rule for UnixTimestamp {
+    fn name(&self) -> &str {
+        "unix_timestamp"
+    }
+
+    fn description(&self) -> &str {
+        "Standard date and datetime for Unix timestamps in time-like fields"
     }
 
-    // Check for Unix timestamps
     // Detection heuristics:
     // 1. Field name contains timestamp-related keywords (time, timestamp, epoch, etc.)
     // 2. Value is all numeric (LU pattern is all 9s)
     // 3. Length is 10 (seconds), 13 (milliseconds), 16 (microseconds), or 19 (nanoseconds)
     // 4. Value is within valid range (2000-2100)
-    if is_timestamp_field(field_name) {
-        if let Some((timestamp_type, std_date, std_datetime)) = parse_unix_timestamp(raw, lu) {
-            assertions.insert("is_unix_timestamp".to_string(), json!(timestamp_type));
-            assertions.insert("std_date".to_string(), json!(std_date));
-            assertions.insert("std_datetime".to_string(), json!(std_datetime));
-        }
+    fn applies(&self, value: &FieldValue) -> bool {
+        is_timestamp_field(value.field_name)
     }
 
-    serde_json::Value::Object(assertions)
+    fn evaluate(&self, value: &FieldValue) -> Option<serde_json::Value> {
+        parse_unix_timestamp(value.raw, value.lu).map(|(timestamp_type, std_date, std_datetime)| {
+            json!({
+                "is_unix_timestamp": timestamp_type,
+                "std_date": std_date,
+                "std_datetime": std_datetime
+            })
+        })
+    }
 }
-
@@ -0,0 +1,307 @@
+// Date parsing that considers every candidate format rather than the first
+// that succeeds, so day/month ambiguity can be resolved per column and reported.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// Order of the day, month and year in a numeric date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    Dmy,
+    Mdy,
+    Ymd,
+}
+
+impl FromStr for DateOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "DMY" => Ok(DateOrder::Dmy),
+            "MDY" => Ok(DateOrder::Mdy),
+            "YMD" => Ok(DateOrder::Ymd),
+            other => Err(format!("Unknown date order '{}': expected DMY, MDY or YMD", other)),
+        }
+    }
+}
+
+/// Formats tried by default, in chrono syntax. Two digit years come before
+/// four digit ones so `31/12/15` is not read as the year 15.
+const BUILTIN_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+    "%Y%m%d",
+    "%d/%m/%y",
+    "%m/%d/%y",
+    "%d/%m/%Y",
+    "%m/%d/%Y",
+    "%d-%m-%Y",
+    "%m-%d-%Y",
+    "%d.%m.%Y",
+    "%d-%b-%Y",
+    "%d %b %Y",
+    "%d %B %Y",
+    "%b %d, %Y",
+    "%B %d, %Y",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M:%SZ",
+    "%Y-%m-%dT%H:%M:%S%.fZ",
+    "%Y-%m-%dT%H:%M:%S%:z",
+    "%Y-%m-%dT%H:%M:%S%.f%:z",
+];
+
+lazy_static! {
+    /// LU masks of the formats above, plus the older `9/9/9` and `9_9_9` shapes
+    static ref DATE_SHAPED_LU: Regex = Regex::new(
+        r"^(?:9[-_./]9[-_./]9|9[- ]Aa[- ]9|Aa 9, 9)(?:[ A]9_9(?:_9)?(?:\.9)?(?:A|[-_]9(?:_9)?)?)?$"
+    )
+    .unwrap();
+    static ref DATE_PARSER: RwLock<DateParser> = RwLock::new(DateParser::default());
+}
+
+/// Whether an LU mask looks like one of the date formats
+pub fn is_date_shaped(lu: &str) -> bool {
+    DATE_SHAPED_LU.is_match(lu)
+}
+
+#[derive(Debug, Clone)]
+struct DateFormat {
+    format: String,
+    /// None when the month is a name, so day and month cannot be confused
+    order: Option<DateOrder>,
+}
+
+impl DateFormat {
+    fn new(format: &str) -> Self {
+        let position = |spec: &str| format.find(spec);
+        let order = match (position("%d"), position("%m"), position("%Y").or_else(|| position("%y"))) {
+            (Some(d), Some(m), Some(y)) if y < m && m < d => Some(DateOrder::Ymd),
+            (Some(d), Some(m), _) if d < m => Some(DateOrder::Dmy),
+            (Some(_), Some(_), _) => Some(DateOrder::Mdy),
+            _ => None,
+        };
+        DateFormat {
+            format: format.to_string(),
+            order,
+        }
+    }
+
+    fn parse(&self, value: &str) -> Option<NaiveDate> {
+        let format = self.format.as_str();
+        if format.contains("%z") || format.contains("%:z") {
+            DateTime::parse_from_str(value, format).ok().map(|dt| dt.naive_local().date())
+        } else if format.contains("%H") {
+            NaiveDateTime::parse_from_str(value, format).ok().map(|dt| dt.date())
+        } else {
+            NaiveDate::parse_from_str(value, format).ok()
+        }
+    }
+}
+
+/// Candidate formats and the order preferred when a value fits several
+#[derive(Debug, Clone)]
+pub struct DateParser {
+    formats: Vec<DateFormat>,
+    prefer: DateOrder,
+    /// Format inferred for each column from all of its values
+    column_formats: HashMap<String, DateFormat>,
+}
+
+impl Default for DateParser {
+    fn default() -> Self {
+        DateParser::new(&[], DateOrder::Dmy)
+    }
+}
+
+impl DateParser {
+    /// The built-in formats, after any `extra_formats`
+    pub fn new(extra_formats: &[&str], prefer: DateOrder) -> Self {
+        let formats = extra_formats
+            .iter()
+            .chain(BUILTIN_FORMATS.iter())
+            .map(|format| DateFormat::new(format))
+            .collect();
+        DateParser {
+            formats,
+            prefer,
+            column_formats: HashMap::new(),
+        }
+    }
+
+    /// Read values of `column` with `format` first, e.g. the format chosen by
+    /// `DateInference::column_format`
+    pub fn set_column_format(&mut self, column: &str, format: &str) {
+        self.column_formats.insert(column.to_string(), DateFormat::new(format));
+    }
+
+    /// The date read with the column's format when it has one and the value
+    /// fits it, otherwise as `parse` reads it
+    pub fn parse_in_column(&self, column: &str, value: &str) -> Option<NaiveDate> {
+        self.column_formats
+            .get(column)
+            .and_then(|format| format.parse(value.trim()))
+            .or_else(|| self.parse(value))
+    }
+
+    /// Every format the value parses with, and the date each gives
+    pub fn candidates(&self, value: &str) -> Vec<(&str, NaiveDate)> {
+        self.formats
+            .iter()
+            .filter_map(|format| format.parse(value).map(|date| (format.format.as_str(), date)))
+            .collect()
+    }
+
+    /// The date read with the preferred order when the value is ambiguous,
+    /// otherwise with the first format that fits
+    pub fn parse(&self, value: &str) -> Option<NaiveDate> {
+        let value = value.trim();
+        let candidates: Vec<(Option<DateOrder>, NaiveDate)> = self
+            .formats
+            .iter()
+            .filter_map(|format| format.parse(value).map(|date| (format.order, date)))
+            .collect();
+        candidates
+            .iter()
+            .find(|(order, _)| *order == Some(self.prefer))
+            .or_else(|| candidates.first())
+            .map(|(_, date)| *date)
+    }
+
+    /// Whether day-first and month-first readings give different dates
+    fn is_ambiguous(&self, candidates: &[(&str, NaiveDate)]) -> bool {
+        let date_for = |order: DateOrder| {
+            candidates.iter().find_map(|(format, date)| {
+                let format = self.formats.iter().find(|f| f.format == *format)?;
+                if format.order == Some(order) {
+                    Some(*date)
+                } else {
+                    None
+                }
+            })
+        };
+        matches!((date_for(DateOrder::Dmy), date_for(DateOrder::Mdy)), (Some(dmy), Some(mdy)) if dmy != mdy)
+    }
+
+    fn preference(&self, format: &str) -> (bool, usize) {
+        let idx = self.formats.iter().position(|f| f.format == format).unwrap_or(usize::MAX);
+        let preferred = self.formats.get(idx).is_some_and(|f| f.order == Some(self.prefer));
+        (!preferred, idx)
+    }
+}
+
+/// Replace the parser used by the `std_date` rules and date inference
+pub fn set_date_parser(parser: DateParser) {
+    *DATE_PARSER.write().unwrap() = parser;
+}
+
+/// Run a closure with the active date parser
+pub fn with_date_parser<T, F: FnOnce(&DateParser) -> T>(f: F) -> T {
+    f(&DATE_PARSER.read().unwrap())
+}
+
+/// Which formats the values of one column fit
+#[derive(Debug, Clone, Default)]
+pub struct DateInference {
+    pub values: usize,
+    pub parsed: usize,
+    /// Values where day-first and month-first readings differ, e.g. 03/04/2015
+    pub ambiguous: usize,
+    /// Values matched per format
+    pub formats: BTreeMap<String, usize>,
+}
+
+impl DateInference {
+    pub fn observe(&mut self, parser: &DateParser, value: &str) {
+        self.values += 1;
+        let candidates = parser.candidates(value.trim());
+        if candidates.is_empty() {
+            return;
+        }
+        self.parsed += 1;
+        if parser.is_ambiguous(&candidates) {
+            self.ambiguous += 1;
+        }
+        // A format can appear once per value even if listed twice
+        let mut seen: Vec<&str> = Vec::new();
+        for (format, _) in candidates {
+            if !seen.contains(&format) {
+                seen.push(format);
+                *self.formats.entry(format.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// The format that fits most values; ties go to the preferred order, then
+    /// to the format listed first
+    pub fn column_format(&self, parser: &DateParser) -> Option<String> {
+        self.formats
+            .iter()
+            .min_by_key(|(format, count)| (std::cmp::Reverse(**count), parser.preference(format)))
+            .map(|(format, _)| format.clone())
+    }
+
+    /// Formats by number of matched values, most first
+    pub fn ranked_formats(&self, parser: &DateParser) -> Vec<(String, usize)> {
+        let mut ranked: Vec<(String, usize)> = self.formats.iter().map(|(f, c)| (f.clone(), *c)).collect();
+        ranked.sort_by_key(|(format, count)| (std::cmp::Reverse(*count), parser.preference(format)));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    #[test]
+    fn test_parse() {
+        let dmy = DateParser::default();
+        assert_eq!(dmy.parse("03/04/2015"), date(2015, 4, 3));
+        assert_eq!(DateParser::new(&[], DateOrder::Mdy).parse("03/04/2015"), date(2015, 3, 4));
+        // Values that only fit one order parse whatever the preference
+        assert_eq!(dmy.parse("12/31/2015"), date(2015, 12, 31));
+        assert_eq!(dmy.parse("31/12/15"), date(2015, 12, 31));
+        assert_eq!(dmy.parse("2015-13-45"), None);
+        assert_eq!(dmy.parse("2015-12-31T10:00:00Z"), date(2015, 12, 31));
+        assert_eq!(dmy.parse("2015-12-31T10:00:00.5+01:00"), date(2015, 12, 31));
+        assert_eq!(dmy.parse("31.12.2015"), date(2015, 12, 31));
+        assert_eq!(dmy.parse("Dec 31, 2015"), date(2015, 12, 31));
+        assert_eq!(DateParser::new(&["%Y%j"], DateOrder::Dmy).parse("2015365"), date(2015, 12, 31));
+    }
+
+    #[test]
+    fn test_column_format() {
+        let mut parser = DateParser::default();
+        let mut column = DateInference::default();
+        for value in ["03/04/2015", "12/31/2015", "05/06/2015", "n/a"] {
+            column.observe(&parser, value);
+        }
+        assert_eq!((column.values, column.parsed, column.ambiguous), (4, 3, 2));
+        parser.set_column_format("joined", &column.column_format(&parser).unwrap());
+        assert_eq!(parser.parse_in_column("joined", "03/04/2015"), date(2015, 3, 4));
+        assert_eq!(parser.parse_in_column("joined", "2015-12-31"), date(2015, 12, 31));
+        assert_eq!(parser.parse_in_column("left", "03/04/2015"), date(2015, 4, 3));
+
+        // A column of only ambiguous values follows the preferred order
+        let mut column = DateInference::default();
+        column.observe(&DateParser::default(), "03/04/2015");
+        assert_eq!(column.column_format(&DateParser::default()).as_deref(), Some("%d/%m/%Y"));
+        assert_eq!(column.column_format(&DateParser::new(&[], DateOrder::Mdy)).as_deref(), Some("%m/%d/%Y"));
+    }
+
+    #[test]
+    fn test_date_shaped_masks() {
+        for shaped in ["9-9-9", "9_9_9", "9.9.9", "Aa 9, 9", "9-Aa-9", "9-9-9A9_9_9A", "9-9-9A9_9_9.9_9_9", "9-9-9 9_9_9"] {
+            assert!(is_date_shaped(shaped), "{}", shaped);
+        }
+        assert!(!is_date_shaped("9.9") && !is_date_shaped("A9 9A") && !is_date_shaped("9"));
+    }
+}
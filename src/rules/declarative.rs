@@ -0,0 +1,300 @@
+// Rules written in YAML or TOML by data stewards, compiled into `Rule`s so
+// they run alongside the built-in assertions in enhanced output.
+
+use crate::config::load_config;
+use crate::rules::registry::{FieldValue, Rule};
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    rules: Vec<RuleSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    name: String,
+    /// Shown next to the name when rules are listed
+    description: Option<String>,
+    /// Key written to `Rules`; defaults to the rule name
+    output: Option<String>,
+    #[serde(default)]
+    when: MatchSpec,
+    assert: Option<AssertSpec>,
+    parse_date: Option<ParseDateSpec>,
+    emit: Option<Value>,
+}
+
+/// All given conditions must hold for the rule to apply
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MatchSpec {
+    /// Regex on the field name
+    field: Option<String>,
+    lu: Option<String>,
+    lu_regex: Option<String>,
+    hu: Option<String>,
+    hu_regex: Option<String>,
+    /// Regex on the raw value
+    raw: Option<String>,
+}
+
+/// All given checks must pass for the assertion to be true
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AssertSpec {
+    regex: Option<String>,
+    #[serde(rename = "in")]
+    one_of: Option<Vec<String>>,
+    range: Option<RangeSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RangeSpec {
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParseDateSpec {
+    formats: Vec<String>,
+}
+
+enum Action {
+    Assert {
+        regex: Option<Regex>,
+        one_of: Option<Vec<String>>,
+        range: Option<(Option<f64>, Option<f64>)>,
+    },
+    ParseDate(Vec<String>),
+    /// A JSON value; strings may contain `{raw}`
+    Emit(Value),
+}
+
+/// A rule loaded from a rules file
+pub struct DeclarativeRule {
+    name: String,
+    description: String,
+    output: String,
+    field: Option<Regex>,
+    lu: Option<String>,
+    lu_regex: Option<Regex>,
+    hu: Option<String>,
+    hu_regex: Option<Regex>,
+    raw: Option<Regex>,
+    action: Action,
+}
+
+/// Load rules from a `.toml` file, or YAML for any other extension
+pub fn load_rules_file<P: AsRef<Path>>(path: P) -> Result<Vec<DeclarativeRule>, Box<dyn std::error::Error>> {
+    compile(load_config(path)?)
+}
+
+pub fn parse_yaml_rules(text: &str) -> Result<Vec<DeclarativeRule>, Box<dyn std::error::Error>> {
+    let file: RuleFile = serde_yaml::from_str(text)?;
+    compile(file)
+}
+
+pub fn parse_toml_rules(text: &str) -> Result<Vec<DeclarativeRule>, Box<dyn std::error::Error>> {
+    let file: RuleFile = toml::from_str(text)?;
+    compile(file)
+}
+
+fn compile(file: RuleFile) -> Result<Vec<DeclarativeRule>, Box<dyn std::error::Error>> {
+    let mut names = std::collections::HashSet::new();
+    if let Some(repeated) = file.rules.iter().find(|spec| !names.insert(spec.name.as_str())) {
+        return Err(format!(
+            "Rule '{}' is defined more than once; rules are enabled and disabled by name, so names must be unique",
+            repeated.name
+        )
+        .into());
+    }
+    file.rules.into_iter().map(DeclarativeRule::compile).collect()
+}
+
+impl DeclarativeRule {
+    fn compile(spec: RuleSpec) -> Result<Self, Box<dyn std::error::Error>> {
+        let name = spec.name;
+        let regex = |pattern: Option<String>| -> Result<Option<Regex>, String> {
+            pattern
+                .map(|p| Regex::new(&p).map_err(|e| format!("Rule '{}': invalid regex '{}': {}", name, p, e)))
+                .transpose()
+        };
+
+        let action = match (spec.assert, spec.parse_date, spec.emit) {
+            (Some(assert), None, None) => {
+                if assert.regex.is_none() && assert.one_of.is_none() && assert.range.is_none() {
+                    return Err(format!("Rule '{}': assert needs regex, in or range", name).into());
+                }
+                Action::Assert {
+                    regex: regex(assert.regex)?,
+                    one_of: assert.one_of,
+                    range: assert.range.map(|r| (r.min, r.max)),
+                }
+            }
+            (None, Some(parse_date), None) => Action::ParseDate(parse_date.formats),
+            (None, None, Some(value)) => Action::Emit(value),
+            _ => {
+                return Err(format!("Rule '{}' needs exactly one of assert, parse_date or emit", name).into());
+            }
+        };
+
+        Ok(DeclarativeRule {
+            description: spec.description.unwrap_or_else(|| "From rules file".to_string()),
+            output: spec.output.unwrap_or_else(|| name.clone()),
+            field: regex(spec.when.field)?,
+            lu: spec.when.lu,
+            lu_regex: regex(spec.when.lu_regex)?,
+            hu: spec.when.hu,
+            hu_regex: regex(spec.when.hu_regex)?,
+            raw: regex(spec.when.raw)?,
+            action,
+            name,
+        })
+    }
+
+    fn parse_date(formats: &[String], raw: &str) -> Option<NaiveDate> {
+        formats.iter().find_map(|format| {
+            NaiveDate::parse_from_str(raw, format)
+                .or_else(|_| NaiveDateTime::parse_from_str(raw, format).map(|dt| dt.date()))
+                .ok()
+        })
+    }
+
+    fn fill_template(value: &Value, raw: &str) -> Value {
+        match value {
+            Value::String(s) => Value::String(s.replace("{raw}", raw)),
+            other => other.clone(),
+        }
+    }
+}
+
+impl Rule for DeclarativeRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn applies(&self, value: &FieldValue) -> bool {
+        let matches = |re: &Option<Regex>, text: &str| re.as_ref().is_none_or(|re| re.is_match(text));
+        let equals = |expected: &Option<String>, text: &str| expected.as_deref().is_none_or(|e| e == text);
+        matches(&self.field, value.field_name)
+            && equals(&self.lu, value.lu)
+            && matches(&self.lu_regex, value.lu)
+            && equals(&self.hu, value.hu)
+            && matches(&self.hu_regex, value.hu)
+            && matches(&self.raw, value.raw)
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<Value> {
+        let raw = value.raw;
+        let result = match &self.action {
+            Action::Assert { regex, one_of, range } => {
+                let regex_ok = regex.as_ref().is_none_or(|re| re.is_match(raw));
+                let in_ok = one_of.as_ref().is_none_or(|list| list.iter().any(|item| item == raw));
+                let range_ok = range.is_none_or(|(min, max)| match raw.trim().parse::<f64>() {
+                    Ok(n) => min.is_none_or(|m| n >= m) && max.is_none_or(|m| n <= m),
+                    Err(_) => false,
+                });
+                json!(regex_ok && in_ok && range_ok)
+            }
+            Action::ParseDate(formats) => json!(Self::parse_date(formats, raw)?.format("%Y-%m-%d").to_string()),
+            Action::Emit(template) => Self::fill_template(template, raw),
+        };
+        let mut entries = serde_json::Map::new();
+        entries.insert(self.output.clone(), result);
+        Some(Value::Object(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::registry::RuleRegistry;
+
+    #[test]
+    fn test_yaml_rules() {
+        let yaml = r#"
+rules:
+  - name: is_order_ref
+    description: Order references look like ORD-123456
+    when: { field: "(?i)order_?ref" }
+    assert: { regex: "^ORD-[0-9]{6}$" }
+  - name: is_sensible_age
+    when: { field: "^age$", lu: "9" }
+    assert: { range: { min: 0, max: 120 } }
+  - name: std_date_dotted
+    output: std_date
+    when: { lu: "9.9.9" }
+    parse_date: { formats: ["%d.%m.%Y", "%Y.%m.%d"] }
+  - name: uk_alias
+    output: std_country_iso3
+    when: { field: country, raw: "^(UK|Britain)$" }
+    emit: GBR
+"#;
+        let mut registry = RuleRegistry::new();
+        for rule in parse_yaml_rules(yaml).unwrap() {
+            registry.try_register(rule).unwrap();
+        }
+
+        assert_eq!(registry.evaluate("OrderRef", "ORD-123456", "A-9", "AAA-999999"), json!({ "is_order_ref": true }));
+        assert_eq!(registry.evaluate("age", "130", "9", "999"), json!({ "is_sensible_age": false }));
+        // The range rule only applies to whole numbers
+        assert_eq!(registry.evaluate("age", "13.5", "9.9", "99.9"), json!({}));
+        assert_eq!(registry.evaluate("dob", "31.12.2015", "9.9.9", "99.99.9999"), json!({ "std_date": "2015-12-31" }));
+        assert_eq!(registry.evaluate("dob", "99.99.2015", "9.9.9", "99.99.9999"), json!({}));
+        assert_eq!(registry.evaluate("country", "UK", "A", "AA"), json!({ "std_country_iso3": "GBR" }));
+
+        let rules = registry.rules();
+        assert_eq!(rules[0].1, "Order references look like ORD-123456");
+        assert_eq!(rules[1].1, "From rules file");
+    }
+
+    #[test]
+    fn test_toml_rules_with_template() {
+        let toml = r#"
+[[rules]]
+name = "is_currency"
+when = { field = "currency" }
+assert = { in = ["GBP", "EUR"] }
+
+[[rules]]
+name = "std_ref"
+when = { field = "ref" }
+emit = "REF-{raw}"
+"#;
+        let mut registry = RuleRegistry::new();
+        for rule in parse_toml_rules(toml).unwrap() {
+            registry.try_register(rule).unwrap();
+        }
+        assert_eq!(registry.evaluate("currency", "USD", "A", "AAA"), json!({ "is_currency": false }));
+        assert_eq!(registry.evaluate("ref", "42", "9", "99"), json!({ "std_ref": "REF-42" }));
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        // Two actions, then a regex that does not compile
+        assert!(parse_yaml_rules("rules:\n  - name: x\n    emit: 1\n    assert: { in: [a] }\n").is_err());
+        assert!(parse_yaml_rules("rules:\n  - name: x\n    when: { field: \"(\" }\n    emit: 1\n").is_err());
+
+        let err = parse_yaml_rules("rules:\n  - name: x\n    emit: 1\n  - name: x\n    emit: 2\n").err().unwrap().to_string();
+        assert!(err.contains("'x' is defined more than once"), "{}", err);
+
+        // File rules cannot replace a built-in rule of the same name
+        let mut registry = RuleRegistry::with_builtin_rules();
+        let rules = parse_yaml_rules("rules:\n  - name: std_date\n    parse_date: { formats: [\"%d.%m.%Y\"] }\n").unwrap();
+        for rule in rules {
+            assert!(registry.try_register(rule).is_err());
+        }
+    }
+}
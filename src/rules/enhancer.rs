@@ -1,11 +1,11 @@
-use crate::rules::assertions::execute_assertions;
+use crate::rules::registry::with_active_rules;
 
 pub fn process_data(field_name: &str, data: &serde_json::Value) -> Option<serde_json::Value> {
     let lu = data["LU"].as_str().unwrap_or("");
     let hu = data["HU"].as_str().unwrap_or("");
     let raw = data["raw"].as_str().unwrap_or("");
 
-    let assertions = execute_assertions(field_name, raw, lu, hu);
+    let assertions = with_active_rules(|rules| rules.evaluate(field_name, raw, lu, hu));
 
     if assertions.as_object().unwrap().is_empty() {
         None
@@ -13,4 +13,3 @@ pub fn process_data(field_name: &str, data: &serde_json::Value) -> Option<serde_
         Some(assertions)
     }
 }
-
@@ -1,3 +1,8 @@
 pub mod enhancer;
 pub mod assertions;
-
+pub mod dates;
+pub mod declarative;
+pub mod postcodes;
+pub mod registry;
+pub mod scorecard;
+pub mod treatment;
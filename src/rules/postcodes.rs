@@ -0,0 +1,173 @@
+// Postal code formats for the major countries, compiled once, used to guess
+// which countries a post* column's values come from and to flag the rest.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// ISO 3166 alpha-2 code and postal code pattern, matched against the
+/// trimmed, upper-cased value. Where formats overlap (e.g. five digits),
+/// earlier countries win ties when picking a column's most likely country.
+/// GB includes the overseas territories' codes, such as Bermuda's `HM 12`.
+const POSTCODE_FORMATS: &[(&str, &str)] = &[
+    ("GB", r"^(([A-Z][A-HJ-Y]?\d[A-Z\d]?|ASCN|STHL|TDCU|BBND|[BFS]IQQ|PCRN|TKCA) ?\d[A-Z]{2}|BFPO ?\d{1,4}|(KY\d|MSR|VG|AI)[ -]?\d{4}|(CR|DD|DV|FL|GE|HM|HS|MA|PG|SB|SN|WK) ?\d{2}|GE ?CX|GIR ?0A{2}|SAN ?TA1)$"),
+    ("US", r"^\d{5}(-\d{4})?$"),
+    ("CA", r"^[ABCEGHJ-NPRSTVXY]\d[ABCEGHJ-NPRSTV-Z] ?\d[ABCEGHJ-NPRSTV-Z]\d$"),
+    ("DE", r"^\d{5}$"),
+    ("FR", r"^(0[1-9]|[1-8]\d|9[0-8])\d{3}$"),
+    ("IT", r"^\d{5}$"),
+    ("ES", r"^(0[1-9]|[1-4]\d|5[0-2])\d{3}$"),
+    ("NL", r"^[1-9]\d{3} ?[A-Z]{2}$"),
+    ("IE", r"^([AC-FHKNPRTV-Y]\d{2}|D6W) ?[0-9AC-FHKNPRTV-Y]{4}$"),
+    ("AU", r"^\d{4}$"),
+    ("BE", r"^[1-9]\d{3}$"),
+    ("AT", r"^[1-9]\d{3}$"),
+    ("CH", r"^[1-9]\d{3}$"),
+    ("DK", r"^[1-9]\d{3}$"),
+    ("NO", r"^\d{4}$"),
+    ("NZ", r"^\d{4}$"),
+    ("ZA", r"^\d{4}$"),
+    ("HU", r"^[1-9]\d{3}$"),
+    ("LU", r"^(L-)?\d{4}$"),
+    ("SE", r"^[1-9]\d{2} ?\d{2}$"),
+    ("CZ", r"^[1-7]\d{2} ?\d{2}$"),
+    ("SK", r"^[089]\d{2} ?\d{2}$"),
+    ("GR", r"^[1-8]\d{2} ?\d{2}$"),
+    ("FI", r"^\d{5}$"),
+    ("MX", r"^\d{5}$"),
+    ("KR", r"^\d{5}$"),
+    ("PL", r"^\d{2}-\d{3}$"),
+    ("PT", r"^[1-9]\d{3}-\d{3}$"),
+    ("BR", r"^\d{5}-?\d{3}$"),
+    ("JP", r"^\d{3}-?\d{4}$"),
+    ("IN", r"^[1-9]\d{5}$"),
+    ("CN", r"^\d{6}$"),
+    ("RU", r"^\d{6}$"),
+    ("SG", r"^\d{6}$"),
+];
+
+/// Examples of values that matched no country, kept per column
+const MAX_EXAMPLES: usize = 3;
+
+lazy_static! {
+    static ref POSTCODE_REGEXES: Vec<(&'static str, Regex)> = POSTCODE_FORMATS
+        .iter()
+        .map(|(country, pattern)| (*country, Regex::new(pattern).unwrap()))
+        .collect();
+}
+
+/// Whether a field name suggests postal codes, e.g. `postcode` or `zip`
+pub fn is_postcode_field(field_name: &str) -> bool {
+    let name = field_name.to_lowercase();
+    name.contains("post") || name.contains("zip")
+}
+
+/// Countries whose postal code format the value fits, in table order
+pub fn matching_countries(value: &str) -> Vec<&'static str> {
+    let value = value.trim().to_uppercase();
+    POSTCODE_REGEXES
+        .iter()
+        .filter(|(_, regex)| regex.is_match(&value))
+        .map(|(country, _)| *country)
+        .collect()
+}
+
+/// Whether the value is a valid postal code in the given country
+pub fn is_postcode_for(country: &str, value: &str) -> bool {
+    let value = value.trim().to_uppercase();
+    POSTCODE_REGEXES
+        .iter()
+        .any(|(code, regex)| *code == country && regex.is_match(&value))
+}
+
+/// Per-country match counts for one column
+#[derive(Debug, Clone, Default)]
+pub struct PostcodeProfile {
+    pub values: usize,
+    pub unmatched: usize,
+    pub unmatched_examples: Vec<String>,
+    /// Values matched per country
+    pub countries: BTreeMap<&'static str, usize>,
+}
+
+impl PostcodeProfile {
+    pub fn observe(&mut self, value: &str) {
+        if value.trim().is_empty() {
+            return;
+        }
+        self.values += 1;
+        let countries = matching_countries(value);
+        if countries.is_empty() {
+            self.unmatched += 1;
+            if self.unmatched_examples.len() < MAX_EXAMPLES && !self.unmatched_examples.iter().any(|e| e == value) {
+                self.unmatched_examples.push(value.to_string());
+            }
+        }
+        for country in countries {
+            *self.countries.entry(country).or_insert(0) += 1;
+        }
+    }
+
+    /// Countries by share of values matched, most likely first
+    pub fn ranked(&self) -> Vec<(&'static str, f64)> {
+        let order = |country: &str| POSTCODE_FORMATS.iter().position(|(code, _)| *code == country);
+        let mut ranked: Vec<(&'static str, usize)> = self.countries.iter().map(|(c, n)| (*c, *n)).collect();
+        ranked.sort_by_key(|(country, count)| (std::cmp::Reverse(*count), order(country)));
+        ranked
+            .into_iter()
+            .map(|(country, count)| (country, count as f64 / self.values as f64))
+            .collect()
+    }
+
+    pub fn most_likely(&self) -> Option<&'static str> {
+        self.ranked().first().map(|(country, _)| *country)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_match_their_country_format() {
+        assert_eq!(matching_countries("SW1A 1AA"), vec!["GB"]);
+        assert_eq!(matching_countries(" sw1a1aa "), vec!["GB"]);
+        assert_eq!(matching_countries("HM 12"), vec!["GB"]);
+        assert_eq!(matching_countries("K1A 0B1"), vec!["CA"]);
+        assert_eq!(matching_countries("1012 AB"), vec!["NL"]);
+        assert_eq!(matching_countries("D02 X285"), vec!["IE"]);
+        assert_eq!(matching_countries("00-950"), vec!["PL"]);
+        // Shared formats match several countries
+        let countries = matching_countries("90210");
+        assert!(countries.contains(&"US") && countries.contains(&"DE"));
+
+        assert!(is_postcode_for("GB", "M1 1AE"));
+        assert!(!is_postcode_for("GB", "90210"));
+        assert!(is_postcode_field("PostCode") && is_postcode_field("billing_zip"));
+        assert!(!is_postcode_field("city"));
+    }
+
+    #[test]
+    fn test_values_matching_no_format() {
+        assert!(matching_countries("ZZ9 9ZZ").is_empty());
+        // Two letters and two digits are only a code for the territory prefixes
+        assert!(matching_countries("AB 12").is_empty());
+    }
+
+    #[test]
+    fn test_profile_ranks_countries_and_keeps_unmatched_examples() {
+        let mut column = PostcodeProfile::default();
+        for value in ["SW1A 1AA", "M1 1AE", "EC1A 1BB", "90210", "n/a", ""] {
+            column.observe(value);
+        }
+        assert_eq!((column.values, column.unmatched), (5, 1));
+        assert_eq!(column.most_likely(), Some("GB"));
+        assert_eq!(column.ranked()[0], ("GB", 0.6));
+        assert_eq!(column.unmatched_examples, vec!["n/a".to_string()]);
+
+        // Ties go to the country listed first
+        let mut column = PostcodeProfile::default();
+        column.observe("90210");
+        assert_eq!(column.most_likely(), Some("US"));
+    }
+}
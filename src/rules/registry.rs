@@ -0,0 +1,229 @@
+use lazy_static::lazy_static;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+/// A value as seen by the rules: the field it came from, the raw text and
+/// its high and low grain Unicode masks
+#[derive(Debug, Clone, Copy)]
+pub struct FieldValue<'a> {
+    pub field_name: &'a str,
+    pub raw: &'a str,
+    pub hu: &'a str,
+    pub lu: &'a str,
+}
+
+/// An assertion rule, run against every value in enhanced output
+pub trait Rule: Send + Sync {
+    /// Name used to enable or disable the rule from the command line
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Whether the rule should run for this value, e.g. based on the field
+    /// name or the masks
+    fn applies(&self, value: &FieldValue) -> bool;
+
+    /// A JSON object whose entries are added to the value's `Rules`, or
+    /// `None` when there is nothing to report
+    fn evaluate(&self, value: &FieldValue) -> Option<Value>;
+}
+
+/// A rule built from closures, for quick additions from library code
+pub struct FnRule<A, E> {
+    name: String,
+    applies: A,
+    evaluate: E,
+}
+
+impl<A, E> FnRule<A, E>
+where
+    A: Fn(&FieldValue) -> bool + Send + Sync,
+    E: Fn(&FieldValue) -> Option<Value> + Send + Sync,
+{
+    pub fn new(name: &str, applies: A, evaluate: E) -> Self {
+        FnRule {
+            name: name.to_string(),
+            applies,
+            evaluate,
+        }
+    }
+}
+
+impl<A, E> Rule for FnRule<A, E>
+where
+    A: Fn(&FieldValue) -> bool + Send + Sync,
+    E: Fn(&FieldValue) -> Option<Value> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn applies(&self, value: &FieldValue) -> bool {
+        (self.applies)(value)
+    }
+
+    fn evaluate(&self, value: &FieldValue) -> Option<Value> {
+        (self.evaluate)(value)
+    }
+}
+
+/// An ordered set of rules. Rules run in registration order, so a later
+/// rule can overwrite a key set by an earlier one.
+#[derive(Default)]
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn Rule>>,
+    disabled: HashSet<String>,
+}
+
+impl RuleRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        RuleRegistry::default()
+    }
+
+    /// The rules bytefreq ships with
+    pub fn with_builtin_rules() -> Self {
+        let mut registry = RuleRegistry::new();
+        for rule in crate::rules::assertions::builtin_rules() {
+            registry.rules.push(rule);
+        }
+        registry
+    }
+
+    pub fn register<R: Rule + 'static>(&mut self, rule: R) {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Register a rule unless one with the same name exists, since rules are
+    /// enabled and disabled by name
+    pub fn try_register<R: Rule + 'static>(&mut self, rule: R) -> Result<(), String> {
+        if self.rules.iter().any(|existing| existing.name() == rule.name()) {
+            return Err(format!(
+                "A rule named '{}' already exists; give the new rule another name (file rules can keep the key with `output`)",
+                rule.name()
+            ));
+        }
+        self.register(rule);
+        Ok(())
+    }
+
+    /// Names and descriptions of all registered rules, and whether each is enabled
+    pub fn rules(&self) -> Vec<(&str, &str, bool)> {
+        self.rules
+            .iter()
+            .map(|rule| (rule.name(), rule.description(), !self.disabled.contains(rule.name())))
+            .collect()
+    }
+
+    /// Disable the named rules
+    pub fn disable(&mut self, names: &[&str]) -> Result<(), String> {
+        self.check_names(names)?;
+        self.disabled.extend(names.iter().map(|name| name.to_string()));
+        Ok(())
+    }
+
+    /// Disable every rule except the named ones
+    pub fn enable_only(&mut self, names: &[&str]) -> Result<(), String> {
+        self.check_names(names)?;
+        self.disabled = self
+            .rules
+            .iter()
+            .map(|rule| rule.name().to_string())
+            .filter(|name| !names.contains(&name.as_str()))
+            .collect();
+        Ok(())
+    }
+
+    fn check_names(&self, names: &[&str]) -> Result<(), String> {
+        let known: Vec<&str> = self.rules.iter().map(|rule| rule.name()).collect();
+        match names.iter().find(|name| !known.contains(name)) {
+            Some(unknown) => Err(format!("Unknown rule '{}'. Rules: {}", unknown, known.join(", "))),
+            None => Ok(()),
+        }
+    }
+
+    /// Run the enabled rules over one value and collect their results into a
+    /// JSON object (empty when no rule reported anything)
+    pub fn evaluate(&self, field_name: &str, raw: &str, lu: &str, hu: &str) -> Value {
+        let mut results = serde_json::Map::new();
+        for (_, result) in self.evaluate_each(field_name, raw, lu, hu) {
+            if let Some(Value::Object(entries)) = result {
+                results.extend(entries);
+            }
+        }
+        Value::Object(results)
+    }
+
+    /// The result of each enabled rule that applies to the value, in order.
+    /// `None` means the rule applied but had nothing to report.
+    pub fn evaluate_each(&self, field_name: &str, raw: &str, lu: &str, hu: &str) -> Vec<(&str, Option<Value>)> {
+        // Remove double quotes from the input strings
+        let value = FieldValue {
+            field_name,
+            raw: raw.trim_matches('"'),
+            lu: lu.trim_matches('"'),
+            hu: hu.trim_matches('"'),
+        };
+
+        self.rules
+            .iter()
+            .filter(|rule| !self.disabled.contains(rule.name()) && rule.applies(&value))
+            .map(|rule| (rule.name(), rule.evaluate(&value)))
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref ACTIVE_RULES: RwLock<RuleRegistry> = RwLock::new(RuleRegistry::with_builtin_rules());
+}
+
+/// Replace the rules used by `process_data`
+pub fn set_active_rules(registry: RuleRegistry) {
+    *ACTIVE_RULES.write().unwrap() = registry;
+}
+
+/// Run a closure with the rules used by `process_data`
+pub fn with_active_rules<T, F: FnOnce(&RuleRegistry) -> T>(f: F) -> T {
+    f(&ACTIVE_RULES.read().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_registered_rule_runs_where_it_applies() {
+        let mut registry = RuleRegistry::with_builtin_rules();
+        registry.register(FnRule::new(
+            "is_sku",
+            |v: &FieldValue| v.field_name == "sku",
+            |v: &FieldValue| Some(json!({ "is_sku": v.lu == "A9" })),
+        ));
+
+        let result = registry.evaluate("sku", "\"AB123\"", "A9", "AA999");
+        assert_eq!(result["is_sku"], json!(true));
+        assert_eq!(result["string_length"], json!(5));
+        assert!(registry.evaluate("name", "x", "a", "a").get("is_sku").is_none());
+
+        let duplicate = FnRule::new("is_sku", |_: &FieldValue| true, |_: &FieldValue| None);
+        assert!(registry.try_register(duplicate).is_err());
+        assert_eq!(registry.rules().iter().filter(|(name, _, _)| *name == "is_sku").count(), 1);
+    }
+
+    #[test]
+    fn test_disable_and_enable_only() {
+        let mut registry = RuleRegistry::with_builtin_rules();
+        registry.disable(&["string_length"]).unwrap();
+        assert!(registry.evaluate("sku", "AB123", "A9", "AA999").get("string_length").is_none());
+
+        registry.enable_only(&["is_numeric"]).unwrap();
+        assert_eq!(registry.evaluate("qty", "12", "9", "99"), json!({ "is_numeric": true }));
+
+        assert!(registry.disable(&["no_such_rule"]).is_err());
+        assert!(registry.enable_only(&["no_such_rule"]).is_err());
+    }
+}
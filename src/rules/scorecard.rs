@@ -0,0 +1,175 @@
+// Assertion results aggregated per column during a DQ run, so a quality
+// summary is available without writing and post-processing enhanced output.
+
+use rand::prelude::*;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Failure examples kept per check
+const MAX_EXAMPLES: usize = 3;
+
+/// Outcome counts for one key a rule writes
+#[derive(Debug, Default, Clone)]
+pub struct KeyScore {
+    pub passed: usize,
+    pub failed: usize,
+    /// Values reported that are not booleans (e.g. a `std_date`)
+    pub produced: usize,
+    /// Smallest and largest numeric value reported, for keys like `string_length`
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Sample of raw values that failed the assertion
+    pub failures: Vec<String>,
+    failures_seen: usize,
+}
+
+/// One rule on one column
+#[derive(Debug, Default, Clone)]
+pub struct RuleScore {
+    /// Values the rule applied to
+    pub applied: usize,
+    /// Values the rule applied to but reported nothing for (e.g. unparseable dates)
+    pub no_result: usize,
+    pub no_result_examples: Vec<String>,
+    no_result_seen: usize,
+    pub keys: BTreeMap<String, KeyScore>,
+}
+
+/// Assertion results per column and rule
+#[derive(Debug, Default)]
+pub struct Scorecard {
+    columns: HashMap<String, BTreeMap<String, RuleScore>>,
+}
+
+impl Scorecard {
+    pub fn new() -> Self {
+        Scorecard::default()
+    }
+
+    /// Record the results of `RuleRegistry::evaluate_each` for one value
+    pub fn observe(&mut self, column: &str, raw: &str, results: &[(&str, Option<Value>)]) {
+        let rules = self.columns.entry(column.to_string()).or_default();
+        for (rule, result) in results {
+            let score = rules.entry(rule.to_string()).or_default();
+            score.applied += 1;
+            let entries = match result {
+                Some(Value::Object(entries)) => entries,
+                _ => {
+                    score.no_result += 1;
+                    sample(&mut score.no_result_examples, &mut score.no_result_seen, raw);
+                    continue;
+                }
+            };
+            for (key, value) in entries {
+                let key_score = score.keys.entry(key.clone()).or_default();
+                match value {
+                    Value::Bool(true) => key_score.passed += 1,
+                    Value::Bool(false) => {
+                        key_score.failed += 1;
+                        sample(&mut key_score.failures, &mut key_score.failures_seen, raw);
+                    }
+                    other => {
+                        key_score.produced += 1;
+                        if let Some(n) = other.as_f64() {
+                            key_score.min = Some(key_score.min.map_or(n, |m| m.min(n)));
+                            key_score.max = Some(key_score.max.map_or(n, |m| m.max(n)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rules scored for a column, by rule name
+    pub fn column(&self, column: &str) -> Option<&BTreeMap<String, RuleScore>> {
+        self.columns.get(column)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+impl KeyScore {
+    /// Share of true results for assertions, or of applied values that
+    /// produced this key otherwise
+    pub fn rate(&self, applied: usize) -> f64 {
+        let (hits, total) = if self.passed + self.failed > 0 {
+            (self.passed, self.passed + self.failed)
+        } else {
+            (self.produced, applied)
+        };
+        if total == 0 {
+            0.0
+        } else {
+            100.0 * hits as f64 / total as f64
+        }
+    }
+
+    pub fn is_assertion(&self) -> bool {
+        self.passed + self.failed > 0
+    }
+}
+
+/// Reservoir sample of up to MAX_EXAMPLES values
+fn sample(examples: &mut Vec<String>, seen: &mut usize, value: &str) {
+    *seen += 1;
+    if examples.len() < MAX_EXAMPLES {
+        examples.push(value.to_string());
+    } else {
+        let slot = thread_rng().gen_range(0..*seen);
+        if slot < MAX_EXAMPLES {
+            examples[slot] = value.to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::registry::RuleRegistry;
+
+    #[test]
+    fn test_scorecard_counts() {
+        let registry = RuleRegistry::with_builtin_rules();
+        let mut scorecard = Scorecard::new();
+        assert!(scorecard.is_empty());
+        for (raw, lu, hu) in [
+            ("M1 1AE", "A9 9A", "A9 9AA"),
+            ("AB1 2CD", "A9 9A", "AA9 9AA"),
+            ("ZZ9 9ZZ", "A9 9A", "AA9 9AA"),
+        ] {
+            scorecard.observe("postcode", raw, &registry.evaluate_each("postcode", raw, lu, hu));
+        }
+        for (raw, lu, hu) in [("2015-12-31", "9-9-9", "9999-99-99"), ("2015-13-45", "9-9-9", "9999-99-99")] {
+            scorecard.observe("joined", raw, &registry.evaluate_each("joined", raw, lu, hu));
+        }
+
+        let postcode = scorecard.column("postcode").unwrap();
+        let uk = &postcode["is_uk_postcode"].keys["is_uk_postcode"];
+        assert_eq!((uk.passed, uk.failed), (2, 1));
+        assert!(uk.is_assertion());
+        assert_eq!(uk.failures, vec!["ZZ9 9ZZ".to_string()]);
+        let length = &postcode["string_length"].keys["string_length"];
+        assert_eq!((length.min, length.max), (Some(6.0), Some(7.0)));
+        assert!(!length.is_assertion());
+
+        // A rule that applies but reports nothing is counted, with examples
+        let date = &scorecard.column("joined").unwrap()["std_date"];
+        assert_eq!((date.applied, date.no_result), (2, 1));
+        assert_eq!(date.keys["std_date"].rate(date.applied), 50.0);
+        assert_eq!(date.no_result_examples, vec!["2015-13-45".to_string()]);
+
+        assert!(scorecard.column("city").is_none());
+    }
+
+    #[test]
+    fn test_failure_examples_are_capped() {
+        let mut examples = Vec::new();
+        let mut seen = 0;
+        for value in ["a", "b", "c", "d", "e"] {
+            sample(&mut examples, &mut seen, value);
+        }
+        assert_eq!((examples.len(), seen), (MAX_EXAMPLES, 5));
+    }
+}
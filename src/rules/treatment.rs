@@ -0,0 +1,332 @@
+// Treatments keyed on (column, mask): the "Data Quality on Read" step where
+// each discovered mask is mapped to a fix, applied during enhanced output.
+
+use crate::config::load_config;
+use chrono::{NaiveDate, NaiveDateTime};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TreatmentFile {
+    /// Grain the masks are written in, `LU` (default) or `HU`
+    grain: Option<String>,
+    treatments: Vec<TreatmentSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TreatmentSpec {
+    name: Option<String>,
+    /// Field name; the treatment applies to any column when absent
+    column: Option<String>,
+    mask: String,
+    action: String,
+    pattern: Option<String>,
+    replacement: Option<String>,
+    text: Option<String>,
+    at: Option<i64>,
+    formats: Option<Vec<String>>,
+    value: Option<Value>,
+}
+
+#[derive(Debug)]
+enum Action {
+    /// The mask is known to be good
+    Keep,
+    /// The value is unusable and becomes null
+    Reject,
+    Set(Value),
+    RegexReplace(Regex, String),
+    /// Insert text at a character offset, counted from the end when negative
+    Insert(String, i64),
+    ParseDate(Vec<String>),
+    Uppercase,
+    Lowercase,
+    Trim,
+}
+
+#[derive(Debug)]
+struct Treatment {
+    name: String,
+    column: Option<String>,
+    mask: String,
+    action: Action,
+}
+
+/// Values touched by one treatment
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TreatmentCount {
+    pub column: Option<String>,
+    pub mask: String,
+    pub values: usize,
+    /// Values the action could not produce a result for, e.g. an invalid date
+    pub failed: usize,
+}
+
+/// Values per column and mask that no treatment matched
+type UntreatedCounts = HashMap<String, HashMap<String, usize>>;
+
+/// A loaded treatment file, with counts of what it touched
+#[derive(Debug)]
+pub struct TreatmentSet {
+    grain: String,
+    treatments: Vec<Treatment>,
+    counts: Vec<AtomicUsize>,
+    failures: Vec<AtomicUsize>,
+    /// One shard per rayon worker, plus one for other threads, so workers
+    /// don't contend for a lock on every untreated value
+    untreated: Vec<Mutex<UntreatedCounts>>,
+}
+
+impl TreatmentSet {
+    /// Load treatments from a `.toml` file, or YAML for any other extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::compile(load_config(path)?)
+    }
+
+    pub fn parse_yaml(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::compile(serde_yaml::from_str(text)?)
+    }
+
+    fn compile(file: TreatmentFile) -> Result<Self, Box<dyn std::error::Error>> {
+        let grain = file.grain.unwrap_or_else(|| "LU".to_string());
+        if grain != "LU" && grain != "HU" {
+            return Err(format!("Treatment grain must be LU or HU, not '{}'", grain).into());
+        }
+        let treatments = file.treatments.into_iter().map(Treatment::compile).collect::<Result<Vec<_>, _>>()?;
+        Ok(TreatmentSet {
+            grain,
+            counts: treatments.iter().map(|_| AtomicUsize::new(0)).collect(),
+            failures: treatments.iter().map(|_| AtomicUsize::new(0)).collect(),
+            untreated: (0..=rayon::current_num_threads()).map(|_| Mutex::new(HashMap::new())).collect(),
+            treatments,
+        })
+    }
+
+    /// Apply the first treatment matching the column and mask. Returns the
+    /// treatment name and the treated value (null when rejected or failed),
+    /// or None when no treatment matched.
+    pub fn apply(&self, column: &str, raw: &str, hu: &str, lu: &str) -> Option<(&str, Value)> {
+        let mask = if self.grain == "HU" { hu } else { lu };
+        let found = self.treatments.iter().position(|t| {
+            t.mask == mask && t.column.as_deref().is_none_or(|c| c == column)
+        });
+        let idx = match found {
+            Some(idx) => idx,
+            None => {
+                self.count_untreated(column, mask);
+                return None;
+            }
+        };
+        let treatment = &self.treatments[idx];
+        let value = treatment.action.apply(raw);
+        self.counts[idx].fetch_add(1, Ordering::Relaxed);
+        if value.is_none() && !matches!(treatment.action, Action::Reject) {
+            self.failures[idx].fetch_add(1, Ordering::Relaxed);
+        }
+        Some((&treatment.name, value.unwrap_or(Value::Null)))
+    }
+
+    /// Count an untreated value in this thread's shard. Names are only
+    /// allocated the first time a column or mask is seen.
+    fn count_untreated(&self, column: &str, mask: &str) {
+        let last = self.untreated.len() - 1;
+        let shard = rayon::current_thread_index().map_or(last, |idx| idx % last);
+        let mut untreated = self.untreated[shard].lock().unwrap();
+        if !untreated.contains_key(column) {
+            untreated.insert(column.to_string(), HashMap::new());
+        }
+        let masks = untreated.get_mut(column).unwrap();
+        match masks.get_mut(mask) {
+            Some(count) => *count += 1,
+            None => {
+                masks.insert(mask.to_string(), 1);
+            }
+        }
+    }
+
+    /// Values touched per treatment, in file order
+    pub fn counts(&self) -> Vec<(&str, TreatmentCount)> {
+        self.treatments
+            .iter()
+            .enumerate()
+            .map(|(idx, t)| {
+                let count = TreatmentCount {
+                    column: t.column.clone(),
+                    mask: t.mask.clone(),
+                    values: self.counts[idx].load(Ordering::Relaxed),
+                    failed: self.failures[idx].load(Ordering::Relaxed),
+                };
+                (t.name.as_str(), count)
+            })
+            .collect()
+    }
+
+    /// Values per (column, mask) that no treatment matched, most frequent first
+    pub fn untreated(&self) -> Vec<(String, String, usize)> {
+        let mut merged: HashMap<(&str, &str), usize> = HashMap::new();
+        let shards: Vec<_> = self.untreated.iter().map(|shard| shard.lock().unwrap()).collect();
+        for shard in &shards {
+            for (column, masks) in shard.iter() {
+                for (mask, count) in masks {
+                    *merged.entry((column, mask)).or_insert(0) += count;
+                }
+            }
+        }
+        let mut untreated: Vec<(String, String, usize)> = merged
+            .into_iter()
+            .map(|((column, mask), count)| (column.to_string(), mask.to_string(), count))
+            .collect();
+        untreated.sort_unstable_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+        untreated
+    }
+}
+
+impl Treatment {
+    fn compile(spec: TreatmentSpec) -> Result<Self, Box<dyn std::error::Error>> {
+        let name = spec.name.clone().unwrap_or_else(|| {
+            format!("{}:{}:{}", spec.column.as_deref().unwrap_or("*"), spec.mask, spec.action)
+        });
+        let action_name = spec.action.clone();
+        let missing = |param: &str| format!("Treatment '{}': {} needs '{}'", name, action_name, param);
+        let action = match action_name.as_str() {
+            "keep" => Action::Keep,
+            "reject" => Action::Reject,
+            "set" => Action::Set(spec.value.ok_or_else(|| missing("value"))?),
+            "regex_replace" => {
+                let pattern = spec.pattern.ok_or_else(|| missing("pattern"))?;
+                let regex = Regex::new(&pattern)
+                    .map_err(|e| format!("Treatment '{}': invalid regex '{}': {}", name, pattern, e))?;
+                Action::RegexReplace(regex, spec.replacement.ok_or_else(|| missing("replacement"))?)
+            }
+            "insert" => Action::Insert(spec.text.ok_or_else(|| missing("text"))?, spec.at.ok_or_else(|| missing("at"))?),
+            "parse_date" => Action::ParseDate(spec.formats.unwrap_or_else(|| vec!["%Y-%m-%d".to_string()])),
+            "uppercase" => Action::Uppercase,
+            "lowercase" => Action::Lowercase,
+            "trim" => Action::Trim,
+            other => {
+                return Err(format!(
+                    "Treatment '{}': unknown action '{}'. Actions: keep, reject, set, regex_replace, insert, parse_date, uppercase, lowercase, trim",
+                    name, other
+                )
+                .into())
+            }
+        };
+        Ok(Treatment {
+            name,
+            column: spec.column,
+            mask: spec.mask,
+            action,
+        })
+    }
+}
+
+impl Action {
+    /// The treated value, or None when rejected or the action failed
+    fn apply(&self, raw: &str) -> Option<Value> {
+        match self {
+            Action::Keep => Some(json!(raw)),
+            Action::Reject => None,
+            Action::Set(value) => Some(value.clone()),
+            Action::RegexReplace(regex, replacement) => Some(json!(regex.replace_all(raw, replacement.as_str()))),
+            Action::Insert(text, at) => {
+                let chars: Vec<char> = raw.chars().collect();
+                let len = chars.len() as i64;
+                let offset = if *at < 0 { len + at } else { *at };
+                if offset < 0 || offset > len {
+                    return None;
+                }
+                let (head, tail) = chars.split_at(offset as usize);
+                Some(json!(format!("{}{}{}", head.iter().collect::<String>(), text, tail.iter().collect::<String>())))
+            }
+            Action::ParseDate(formats) => formats.iter().find_map(|format| {
+                NaiveDate::parse_from_str(raw, format)
+                    .or_else(|_| NaiveDateTime::parse_from_str(raw, format).map(|dt| dt.date()))
+                    .ok()
+                    .map(|date| json!(date.format("%Y-%m-%d").to_string()))
+            }),
+            Action::Uppercase => Some(json!(raw.to_uppercase())),
+            Action::Lowercase => Some(json!(raw.to_lowercase())),
+            Action::Trim => Some(json!(raw.trim())),
+        }
+    }
+}
+
+lazy_static! {
+    static ref ACTIVE_TREATMENTS: RwLock<Option<TreatmentSet>> = RwLock::new(None);
+}
+
+/// Replace the treatments applied in enhanced output
+pub fn set_active_treatments(treatments: TreatmentSet) {
+    *ACTIVE_TREATMENTS.write().unwrap() = Some(treatments);
+}
+
+/// Run a closure with the active treatments, if any were loaded
+pub fn with_active_treatments<T, F: FnOnce(Option<&TreatmentSet>) -> T>(f: F) -> T {
+    f(ACTIVE_TREATMENTS.read().unwrap().as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_treatments_by_column_and_mask() {
+        let treatments = TreatmentSet::parse_yaml(
+            r#"
+treatments:
+  - { name: postcode_space, column: postcode, mask: A9A9A, action: insert, text: " ", at: -3 }
+  - { column: joined, mask: 9-9-9, action: parse_date }
+  - { mask: A9 9A, action: keep }
+  - { name: no_dashes, column: phone, mask: 9-9, action: regex_replace, pattern: "-", replacement: "" }
+  - { column: status, mask: "_", action: set, value: unknown }
+  - { column: status, mask: "9", action: reject }
+  - { column: address.postcode, mask: A9A9A, action: lowercase }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(treatments.apply("postcode", "SW1A1AA", "AA9A9AA", "A9A9A"), Some(("postcode_space", json!("SW1A 1AA"))));
+        // A treatment without a column applies to any column
+        assert_eq!(treatments.apply("postcode", "M1 1AE", "A9 9AA", "A9 9A"), Some(("*:A9 9A:keep", json!("M1 1AE"))));
+        // A nested path is a column of its own
+        assert_eq!(treatments.apply("address.postcode", "SW1A1AA", "AA9A9AA", "A9A9A").unwrap().1, json!("sw1a1aa"));
+        assert_eq!(treatments.apply("joined", "2015-12-31", "9999-99-99", "9-9-9").unwrap().1, json!("2015-12-31"));
+        assert_eq!(treatments.apply("phone", "0161-4960000", "9999-9999999", "9-9").unwrap().1, json!("01614960000"));
+        assert_eq!(treatments.apply("status", "", "", "_").unwrap().1, json!("unknown"));
+
+        // Failed and rejected values are null, but only failures are counted as such
+        assert_eq!(treatments.apply("joined", "2015-13-45", "9999-99-99", "9-9-9").unwrap().1, Value::Null);
+        assert_eq!(treatments.apply("status", "7", "9", "9").unwrap().1, Value::Null);
+        let counts = treatments.counts();
+        assert_eq!((counts[1].1.values, counts[1].1.failed), (2, 1));
+        assert_eq!((counts[5].1.values, counts[5].1.failed), (1, 0));
+    }
+
+    #[test]
+    fn test_untreated_values_are_counted_across_threads() {
+        let treatments = TreatmentSet::parse_yaml("treatments:\n  - { column: postcode, mask: A9 9A, action: keep }\n").unwrap();
+        (0..1000).into_par_iter().for_each(|_| {
+            assert_eq!(treatments.apply("joined", "SW1A1AA", "AA9A9AA", "A9A9A"), None);
+        });
+        assert_eq!(treatments.apply("postcode", "n/a", "a/a", "a/a"), None);
+        assert_eq!(
+            treatments.untreated(),
+            vec![("joined".to_string(), "A9A9A".to_string(), 1000), ("postcode".to_string(), "a/a".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_treatments_are_rejected() {
+        assert!(TreatmentSet::parse_yaml("treatments:\n  - { mask: '9', action: insert }\n").is_err());
+        assert!(TreatmentSet::parse_yaml("treatments:\n  - { mask: '9', action: explode }\n").is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+
+/// Byte-level diagnostics about how a text file is laid out: BOM, line endings,
+/// line lengths and embedded NULs. These are lost once lines are split and
+/// decoded, so they are gathered from the raw bytes as the input is read.
+#[derive(Debug, Default, Clone)]
+pub struct FileStructure {
+    pub bom: Option<&'static str>,
+    pub bytes: usize,
+    pub lines: usize,
+    pub lf_endings: usize,
+    pub crlf_endings: usize,
+    /// Carriage returns not followed by a line feed (classic Mac line endings)
+    pub cr_endings: usize,
+    pub empty_lines: usize,
+    /// Longest line in bytes, excluding its line terminator
+    pub longest_line: usize,
+    /// 1-based line number of the longest line
+    pub longest_line_number: usize,
+    pub trailing_newline: bool,
+    pub nul_bytes: usize,
+    pub lines_with_nul: usize,
+    pub invalid_utf8_lines: usize,
+}
+
+impl FileStructure {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan a complete buffer, splitting it on `\n`.
+    pub fn scan(data: &[u8]) -> Self {
+        let mut structure = Self::new();
+        for line in data.split_inclusive(|&b| b == b'\n') {
+            structure.observe_line(line);
+        }
+        structure
+    }
+
+    /// Record one raw line as returned by `read_until(b'\n')`, i.e. including
+    /// its terminating `\n` when present.
+    pub fn observe_line(&mut self, raw: &[u8]) {
+        if raw.is_empty() {
+            return;
+        }
+
+        let mut content = raw;
+        if self.lines == 0 {
+            if let Some((name, len)) = Self::detect_bom(content) {
+                self.bom = Some(name);
+                content = &content[len..];
+            }
+        }
+
+        self.bytes += raw.len();
+        self.lines += 1;
+        self.trailing_newline = raw.last() == Some(&b'\n');
+
+        if content.last() == Some(&b'\n') {
+            content = &content[..content.len() - 1];
+            if content.last() == Some(&b'\r') {
+                content = &content[..content.len() - 1];
+                self.crlf_endings += 1;
+            } else {
+                self.lf_endings += 1;
+            }
+        }
+
+        // Lone carriage returns inside the remaining content act as line breaks
+        // for tools that honour classic Mac endings.
+        self.cr_endings += content.iter().filter(|&&b| b == b'\r').count();
+
+        if content.is_empty() {
+            self.empty_lines += 1;
+        }
+        if content.len() > self.longest_line {
+            self.longest_line = content.len();
+            self.longest_line_number = self.lines;
+        }
+
+        let nuls = content.iter().filter(|&&b| b == 0).count();
+        if nuls > 0 {
+            self.nul_bytes += nuls;
+            self.lines_with_nul += 1;
+        }
+
+        if std::str::from_utf8(content).is_err() {
+            self.invalid_utf8_lines += 1;
+        }
+    }
+
+    fn detect_bom(data: &[u8]) -> Option<(&'static str, usize)> {
+        if data.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            Some(("UTF-8", 3))
+        } else if data.starts_with(&[0xFF, 0xFE]) {
+            Some(("UTF-16LE", 2))
+        } else if data.starts_with(&[0xFE, 0xFF]) {
+            Some(("UTF-16BE", 2))
+        } else {
+            None
+        }
+    }
+
+    /// Combine the diagnostics of another input, e.g. when several files are
+    /// profiled together. The BOM is the first one seen; line numbers of the
+    /// longest line refer to the combined stream.
+    pub fn merge(&mut self, other: &FileStructure) {
+        if self.bom.is_none() {
+            self.bom = other.bom;
+        }
+        if other.longest_line > self.longest_line {
+            self.longest_line = other.longest_line;
+            self.longest_line_number = self.lines + other.longest_line_number;
+        }
+        self.bytes += other.bytes;
+        self.lines += other.lines;
+        self.lf_endings += other.lf_endings;
+        self.crlf_endings += other.crlf_endings;
+        self.cr_endings += other.cr_endings;
+        self.empty_lines += other.empty_lines;
+        self.trailing_newline = other.trailing_newline;
+        self.nul_bytes += other.nul_bytes;
+        self.lines_with_nul += other.lines_with_nul;
+        self.invalid_utf8_lines += other.invalid_utf8_lines;
+    }
+
+    /// True when more than one kind of line ending was seen
+    pub fn mixed_line_endings(&self) -> bool {
+        [self.lf_endings, self.crlf_endings, self.cr_endings]
+            .iter()
+            .filter(|&&n| n > 0)
+            .count()
+            > 1
+    }
+}
+
+impl fmt::Display for FileStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "FileStructure:")?;
+        writeln!(f, "BOM: {}", self.bom.unwrap_or("none"))?;
+        writeln!(f, "Bytes: {}", self.bytes)?;
+        writeln!(
+            f,
+            "Line endings: LF {}, CRLF {}, CR {}{}",
+            self.lf_endings,
+            self.crlf_endings,
+            self.cr_endings,
+            if self.mixed_line_endings() { " (mixed)" } else { "" }
+        )?;
+        writeln!(f, "Lines: {} (empty: {})", self.lines, self.empty_lines)?;
+        writeln!(
+            f,
+            "Longest line: {} bytes (line {})",
+            self.longest_line, self.longest_line_number
+        )?;
+        writeln!(
+            f,
+            "Trailing newline: {}",
+            if self.trailing_newline { "yes" } else { "no" }
+        )?;
+        writeln!(
+            f,
+            "NUL bytes: {} (in {} lines)",
+            self.nul_bytes, self.lines_with_nul
+        )?;
+        write!(f, "Invalid UTF-8 lines: {}", self.invalid_utf8_lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_mixed_endings() {
+        let data = b"\xEF\xBB\xBFa|b\r\n1|2\n\n3\x00|4\rx";
+        let s = FileStructure::scan(data);
+        assert_eq!(s.bom, Some("UTF-8"));
+        assert_eq!(s.lines, 4);
+        assert_eq!(s.crlf_endings, 1);
+        assert_eq!(s.lf_endings, 2);
+        assert_eq!(s.cr_endings, 1);
+        assert!(s.mixed_line_endings());
+        assert_eq!(s.empty_lines, 1);
+        assert_eq!(s.longest_line, 6);
+        assert_eq!(s.longest_line_number, 4);
+        assert!(!s.trailing_newline);
+        assert_eq!(s.nul_bytes, 1);
+        assert_eq!(s.lines_with_nul, 1);
+    }
+
+    #[test]
+    fn test_scan_clean_file() {
+        let s = FileStructure::scan(b"a\nb\n");
+        assert_eq!(s.bom, None);
+        assert_eq!(s.lf_endings, 2);
+        assert!(!s.mixed_line_endings());
+        assert!(s.trailing_newline);
+        assert_eq!(s.invalid_utf8_lines, 0);
+    }
+}
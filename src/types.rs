@@ -0,0 +1,434 @@
+// Recommended column types, inferred from the LU masks of the values seen in
+// the DQ pass and written out as a report section or a schema. LEARN and
+// VALIDATE read their column types from here too.
+
+use crate::rules::dates::{is_date_shaped, DateInference, DateParser};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Distinct values tracked per column before giving up on enum and identifier detection
+const MAX_DISTINCT: usize = 1000;
+/// Enums have at most this many values
+const MAX_ENUM_VALUES: usize = 20;
+/// Counter-examples kept per value kind
+const MAX_EXAMPLES: usize = 3;
+/// Most digits a SQL DECIMAL or an Arrow decimal128 can hold
+const MAX_DECIMAL_PRECISION: usize = 38;
+/// Words that are booleans wherever they appear
+const BOOLEAN_WORDS: [&str; 4] = ["true", "false", "yes", "no"];
+/// Letters that are only booleans when every value in the column is one of
+/// these or a boolean word; otherwise they are just text
+const BOOLEAN_LETTERS: [&str; 4] = ["t", "f", "y", "n"];
+
+/// What a single value looks like
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum ValueKind {
+    Boolean,
+    Integer,
+    Decimal,
+    Date,
+    Datetime,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InferredType {
+    /// Largest number of digits seen
+    Integer { digits: usize },
+    Decimal { precision: usize, scale: usize },
+    Boolean,
+    Date { format: Option<String> },
+    Datetime { format: Option<String> },
+    /// A small set of repeating values, most frequent first
+    Enum { values: Vec<String> },
+    /// Unique codes, kept as text, e.g. keys with leading zeros
+    Identifier,
+    Text,
+}
+
+impl fmt::Display for InferredType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InferredType::Integer { digits } => write!(f, "integer({})", digits),
+            InferredType::Decimal { precision, scale } => write!(f, "decimal({},{})", precision, scale),
+            InferredType::Boolean => write!(f, "boolean"),
+            InferredType::Date { format } => write!(f, "date {}", format.as_deref().unwrap_or("")),
+            InferredType::Datetime { format } => write!(f, "datetime {}", format.as_deref().unwrap_or("")),
+            InferredType::Enum { values } => write!(f, "enum({})", values.len()),
+            InferredType::Identifier => write!(f, "identifier"),
+            InferredType::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// The recommendation for one column
+#[derive(Debug, Clone)]
+pub struct TypeSuggestion {
+    pub name: String,
+    pub inferred: InferredType,
+    /// Share of non-empty values that fit the type
+    pub confidence: f64,
+    /// Sample of non-empty values that do not fit
+    pub counter_examples: Vec<String>,
+    pub nullable: bool,
+    /// Longest value in characters
+    pub max_length: usize,
+}
+
+/// Value shapes seen in one column
+#[derive(Debug, Clone, Default)]
+pub struct TypeProfile {
+    values: usize,
+    nulls: usize,
+    kinds: HashMap<ValueKind, usize>,
+    examples: HashMap<ValueKind, Vec<String>>,
+    integer_digits: usize,
+    /// Digits before and after the point, for decimals
+    whole_digits: usize,
+    scale: usize,
+    leading_zeros: bool,
+    has_whitespace: bool,
+    max_length: usize,
+    distinct: HashMap<String, usize>,
+    distinct_overflow: bool,
+    dates: DateInference,
+}
+
+impl TypeProfile {
+    /// Record a value and the LU mask of its trimmed text
+    pub fn observe(&mut self, parser: &DateParser, raw: &str, lu: &str) {
+        self.values += 1;
+        let value = raw.trim();
+        if value.is_empty() {
+            self.nulls += 1;
+            return;
+        }
+        self.max_length = self.max_length.max(value.chars().count());
+        self.has_whitespace |= value.chars().any(char::is_whitespace);
+        if !self.distinct_overflow {
+            *self.distinct.entry(value.to_string()).or_insert(0) += 1;
+            if self.distinct.len() > MAX_DISTINCT {
+                self.distinct_overflow = true;
+                self.distinct.clear();
+            }
+        }
+
+        let kind = self.classify(parser, value, lu);
+        *self.kinds.entry(kind).or_insert(0) += 1;
+        let examples = self.examples.entry(kind).or_default();
+        if examples.len() < MAX_EXAMPLES && !examples.iter().any(|e| e == value) {
+            examples.push(value.to_string());
+        }
+    }
+
+    // Numbers are told apart by their mask, and dates need a mask the
+    // `std_date` rule treats as date-shaped as well as a format that fits
+    fn classify(&mut self, parser: &DateParser, value: &str, lu: &str) -> ValueKind {
+        let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+        let unsigned_mask = lu.strip_prefix(['-', '+']).unwrap_or(lu);
+        let digits = |mask: &str| !mask.is_empty() && mask.chars().all(|c| c == '9');
+        if BOOLEAN_WORDS.contains(&value.to_lowercase().as_str()) {
+            ValueKind::Boolean
+        } else if digits(unsigned_mask) {
+            let length = unsigned.chars().count();
+            self.integer_digits = self.integer_digits.max(length);
+            self.leading_zeros |= length > 1 && unsigned.starts_with('0');
+            ValueKind::Integer
+        } else if unsigned_mask
+            .split_once('.')
+            .is_some_and(|(whole, fraction)| (whole.is_empty() || digits(whole)) && digits(fraction))
+        {
+            let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+            self.whole_digits = self.whole_digits.max(whole.trim_start_matches('0').chars().count());
+            self.scale = self.scale.max(fraction.chars().count());
+            ValueKind::Decimal
+        } else if is_date_shaped(lu) {
+            let candidates = parser.candidates(value);
+            if candidates.is_empty() {
+                ValueKind::Text
+            } else {
+                self.dates.observe(parser, value);
+                if candidates.iter().any(|(format, _)| format.contains("%H")) {
+                    ValueKind::Datetime
+                } else {
+                    ValueKind::Date
+                }
+            }
+        } else {
+            ValueKind::Text
+        }
+    }
+
+    fn count(&self, kind: ValueKind) -> usize {
+        self.kinds.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// The recommended type, and the value kinds that fit it
+    fn recommend(&self, parser: &DateParser) -> (InferredType, Vec<ValueKind>) {
+        use ValueKind::*;
+        let non_null = self.values - self.nulls;
+        let numeric = self.count(Integer) + self.count(Decimal);
+        let dates = self.count(Date) + self.count(Datetime);
+        let mut kinds: Vec<(ValueKind, usize)> = self.kinds.iter().map(|(k, c)| (*k, *c)).collect();
+        kinds.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let dominant = kinds.first().map(|(kind, _)| *kind);
+        let distinct = if self.distinct_overflow { None } else { Some(self.distinct.len()) };
+
+        let boolean_letters = distinct.is_some()
+            && self.distinct.keys().any(|v| BOOLEAN_LETTERS.contains(&v.to_lowercase().as_str()))
+            && self.distinct.keys().all(|v| {
+                let v = v.to_lowercase();
+                BOOLEAN_LETTERS.contains(&v.as_str()) || BOOLEAN_WORDS.contains(&v.as_str())
+            });
+
+        let (inferred, fits) = match dominant {
+            None => (InferredType::Text, vec![]),
+            _ if boolean_letters => (InferredType::Boolean, vec![Boolean, Text]),
+            Some(Integer) | Some(Decimal) if numeric >= dates && numeric >= self.count(Text) && numeric >= self.count(Boolean) => {
+                let zero_one = distinct.is_some() && self.distinct.keys().all(|v| v == "0" || v == "1");
+                if self.count(Decimal) == 0 && zero_one && self.count(Integer) == non_null {
+                    (InferredType::Boolean, vec![Integer, Boolean])
+                } else if self.leading_zeros && self.count(Decimal) == 0 {
+                    (InferredType::Identifier, vec![Integer, Text])
+                } else if self.count(Decimal) > 0 {
+                    let whole = self.whole_digits.max(self.integer_digits).max(1);
+                    (InferredType::Decimal { precision: whole + self.scale, scale: self.scale }, vec![Integer, Decimal])
+                } else {
+                    (InferredType::Integer { digits: self.integer_digits }, vec![Integer])
+                }
+            }
+            Some(Date) | Some(Datetime) if dates >= self.count(Text) => {
+                let format = self.dates.column_format(parser);
+                if self.count(Datetime) > 0 {
+                    (InferredType::Datetime { format }, vec![Date, Datetime])
+                } else {
+                    (InferredType::Date { format }, vec![Date])
+                }
+            }
+            Some(Boolean) => (InferredType::Boolean, vec![Boolean]),
+            _ => {
+                let all = vec![Boolean, Integer, Decimal, Date, Datetime, Text];
+                match distinct {
+                    Some(d) if d <= MAX_ENUM_VALUES && non_null >= 2 * d => {
+                        let mut values: Vec<(&String, &usize)> = self.distinct.iter().collect();
+                        values.sort_unstable_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+                        let values = values.into_iter().map(|(v, _)| v.clone()).collect();
+                        (InferredType::Enum { values }, all)
+                    }
+                    Some(d) if d == non_null && non_null >= 10 && !self.has_whitespace => (InferredType::Identifier, all),
+                    _ => (InferredType::Text, all),
+                }
+            }
+        };
+        (inferred, fits)
+    }
+
+    /// The recommended type, or None when every value was empty
+    pub fn inferred_type(&self, parser: &DateParser) -> Option<InferredType> {
+        if self.values == self.nulls {
+            None
+        } else {
+            Some(self.recommend(parser).0)
+        }
+    }
+
+    pub fn suggest(&self, name: &str, parser: &DateParser) -> TypeSuggestion {
+        let (inferred, fits) = self.recommend(parser);
+        let non_null = self.values - self.nulls;
+        let fitting: usize = fits.iter().map(|kind| self.count(*kind)).sum();
+        let mut misfits: Vec<&ValueKind> = self.kinds.keys().filter(|kind| !fits.contains(kind)).collect();
+        misfits.sort_unstable_by_key(|kind| std::cmp::Reverse(self.count(**kind)));
+        let counter_examples = misfits
+            .into_iter()
+            .flat_map(|kind| self.examples.get(kind).cloned().unwrap_or_default())
+            .take(MAX_EXAMPLES)
+            .collect();
+        TypeSuggestion {
+            name: name.to_string(),
+            inferred,
+            confidence: if non_null == 0 { 0.0 } else { fitting as f64 / non_null as f64 },
+            counter_examples,
+            nullable: self.nulls > 0 || self.values == 0,
+            max_length: self.max_length,
+        }
+    }
+}
+
+impl TypeSuggestion {
+    /// Digits needed when more than a decimal type can hold, so the schema
+    /// falls back to text
+    fn excess_precision(&self) -> Option<usize> {
+        match &self.inferred {
+            InferredType::Integer { digits: precision } | InferredType::Decimal { precision, .. }
+                if *precision > MAX_DECIMAL_PRECISION =>
+            {
+                Some(*precision)
+            }
+            _ => None,
+        }
+    }
+
+    fn sql_type(&self) -> String {
+        let varchar = format!("VARCHAR({})", self.max_length.max(1));
+        if self.excess_precision().is_some() {
+            return varchar;
+        }
+        match &self.inferred {
+            InferredType::Integer { digits } if *digits <= 9 => "INTEGER".to_string(),
+            InferredType::Integer { digits } if *digits <= 18 => "BIGINT".to_string(),
+            InferredType::Integer { digits } => format!("DECIMAL({},0)", digits),
+            InferredType::Decimal { precision, scale } => format!("DECIMAL({},{})", precision, scale),
+            InferredType::Boolean => "BOOLEAN".to_string(),
+            InferredType::Date { .. } => "DATE".to_string(),
+            InferredType::Datetime { .. } => "TIMESTAMP".to_string(),
+            InferredType::Enum { .. } | InferredType::Identifier | InferredType::Text => varchar,
+        }
+    }
+
+    fn arrow_type(&self) -> Value {
+        if self.excess_precision().is_some() {
+            return json!({ "name": "utf8" });
+        }
+        match &self.inferred {
+            InferredType::Integer { digits } if *digits <= 18 => {
+                json!({ "name": "int", "bitWidth": if *digits <= 9 { 32 } else { 64 }, "isSigned": true })
+            }
+            InferredType::Integer { digits } => json!({ "name": "decimal", "precision": digits, "scale": 0, "bitWidth": 128 }),
+            InferredType::Decimal { precision, scale } => {
+                json!({ "name": "decimal", "precision": precision, "scale": scale, "bitWidth": 128 })
+            }
+            InferredType::Boolean => json!({ "name": "bool" }),
+            InferredType::Date { .. } => json!({ "name": "date", "unit": "DAY" }),
+            InferredType::Datetime { .. } => json!({ "name": "timestamp", "unit": "MICROSECOND", "timezone": null }),
+            InferredType::Enum { .. } | InferredType::Identifier | InferredType::Text => json!({ "name": "utf8" }),
+        }
+    }
+}
+
+/// A SQL `CREATE TABLE` statement. Columns where not every value fits get a
+/// comment with the share that does.
+pub fn sql_schema(table: &str, columns: &[TypeSuggestion]) -> String {
+    let lines: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| {
+            let comma = if idx + 1 < columns.len() { "," } else { "" };
+            let not_null = if column.nullable { "" } else { " NOT NULL" };
+            let mut line = format!("    \"{}\" {}{}{}", column.name.replace('"', "\"\""), column.sql_type(), not_null, comma);
+            let mut notes = Vec::new();
+            if column.confidence < 1.0 {
+                notes.push(format!("{:.1}% of values fit", column.confidence * 100.0));
+            }
+            if let Some(precision) = column.excess_precision() {
+                notes.push(format!("{} digits is more than DECIMAL's {}", precision, MAX_DECIMAL_PRECISION));
+            }
+            if let InferredType::Enum { values } = &column.inferred {
+                notes.push(format!("values: {}", values.join(", ")));
+            }
+            if !notes.is_empty() {
+                line.push_str(&format!(" -- {}", notes.join("; ")));
+            }
+            line
+        })
+        .collect();
+    format!("CREATE TABLE \"{}\" (\n{}\n);\n", table.replace('"', "\"\""), lines.join("\n"))
+}
+
+/// An Arrow schema in the JSON form used by Arrow's integration tests
+pub fn arrow_schema(columns: &[TypeSuggestion]) -> Value {
+    let fields: Vec<Value> = columns
+        .iter()
+        .map(|column| {
+            let mut field = json!({
+                "name": column.name,
+                "nullable": column.nullable,
+                "type": column.arrow_type(),
+                "children": [],
+            });
+            if let InferredType::Enum { values } = &column.inferred {
+                field["metadata"] = json!([{ "key": "bytefreq.enum_values", "value": values.join(",") }]);
+            }
+            field
+        })
+        .collect();
+    json!({ "fields": fields })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mask::mask_value;
+
+    fn suggest(values: &[&str]) -> TypeSuggestion {
+        let parser = DateParser::default();
+        let mut profile = TypeProfile::default();
+        for value in values {
+            profile.observe(&parser, value, &mask_value(value.trim(), "LU", "col"));
+        }
+        profile.suggest("col", &parser)
+    }
+
+    #[test]
+    fn test_numbers_and_booleans() {
+        assert_eq!(suggest(&["1", "22", "-333"]).inferred, InferredType::Integer { digits: 3 });
+        let amount = suggest(&["1.5", "22", "3.25", "n/a", ""]);
+        assert_eq!(amount.inferred, InferredType::Decimal { precision: 4, scale: 2 });
+        assert_eq!((amount.confidence, amount.nullable), (0.75, true));
+        assert_eq!(amount.counter_examples, vec!["n/a".to_string()]);
+        assert_eq!(suggest(&["007", "012"]).inferred, InferredType::Identifier);
+
+        assert_eq!(suggest(&["0", "1", "1"]).inferred, InferredType::Boolean);
+        assert_eq!(suggest(&["Yes", "no"]).inferred, InferredType::Boolean);
+        assert_eq!(suggest(&["Y", "N", "n", "yes"]).inferred, InferredType::Boolean);
+        // Single letters are only booleans when the whole column is
+        let grades = suggest(&["A", "B", "F", "A", "B", "F", "C", "A"]);
+        assert!(matches!(grades.inferred, InferredType::Enum { .. }), "{:?}", grades.inferred);
+        assert_eq!(grades.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_dates_enums_identifiers_and_text() {
+        assert_eq!(
+            suggest(&["12/31/2015", "03/04/2015"]).inferred,
+            InferredType::Date { format: Some("%m/%d/%Y".to_string()) }
+        );
+        assert!(matches!(suggest(&["2015-12-31T10:00:00Z"]).inferred, InferredType::Datetime { .. }));
+        // %Y%m%d parses this, but its mask is a number's
+        assert_eq!(suggest(&["20151231"]).inferred, InferredType::Integer { digits: 8 });
+
+        assert_eq!(
+            suggest(&["red", "blue", "red", "red", "blue"]).inferred,
+            InferredType::Enum { values: vec!["red".to_string(), "blue".to_string()] }
+        );
+        let codes: Vec<String> = (0..12).map(|i| format!("AB{}X", i)).collect();
+        let codes: Vec<&str> = codes.iter().map(|s| s.as_str()).collect();
+        assert_eq!(suggest(&codes).inferred, InferredType::Identifier);
+        assert_eq!(suggest(&["some free text", "more text"]).inferred, InferredType::Text);
+
+        let parser = DateParser::default();
+        let mut profile = TypeProfile::default();
+        profile.observe(&parser, " ", "_");
+        assert_eq!(profile.inferred_type(&parser), None);
+    }
+
+    #[test]
+    fn test_sql_and_arrow_schemas() {
+        let columns = vec![suggest(&["1", "2"]), suggest(&["1.5", "22", "3.25", "n/a", ""])];
+        let sql = sql_schema("orders", &columns);
+        assert!(sql.contains("\"col\" INTEGER NOT NULL,"));
+        assert!(sql.contains("DECIMAL(4,2) -- 75.0% of values fit"));
+        assert_eq!(arrow_schema(&columns)["fields"][1]["type"]["precision"], json!(4));
+
+        // Precision above 38 falls back to text
+        let wide = "1".repeat(30) + "." + &"5".repeat(10);
+        let columns = vec![suggest(&[&wide]), suggest(&[&"9".repeat(40)])];
+        assert_eq!(columns[0].inferred, InferredType::Decimal { precision: 40, scale: 10 });
+        let sql = sql_schema("wide", &columns);
+        assert!(sql.contains("VARCHAR(41) NOT NULL, -- 40 digits is more than DECIMAL's 38"), "{}", sql);
+        assert!(sql.contains("VARCHAR(40) NOT NULL -- 40 digits"), "{}", sql);
+        let arrow = arrow_schema(&columns);
+        assert_eq!(arrow["fields"][0]["type"], json!({ "name": "utf8" }));
+        assert_eq!(arrow["fields"][1]["type"], json!({ "name": "utf8" }));
+    }
+}
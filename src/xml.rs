@@ -0,0 +1,299 @@
+#[cfg(feature = "xml")]
+use quick_xml::events::{BytesStart, Event};
+#[cfg(feature = "xml")]
+use quick_xml::Reader;
+#[cfg(feature = "xml")]
+use serde_json::{Map, Value};
+#[cfg(feature = "xml")]
+use std::collections::HashSet;
+use std::io::BufRead;
+
+#[cfg(feature = "xml")]
+/// XML reader that turns each record element into a JSON object
+pub struct XmlReader;
+
+#[cfg(feature = "xml")]
+/// An element under construction while reading
+struct Frame {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+    has_children: bool,
+}
+
+#[cfg(feature = "xml")]
+impl XmlReader {
+    /// Read an XML document and return one JSON line per record element.
+    /// This feeds into the existing JSON processing pipeline. All records are
+    /// held in memory, as repeated elements are only known once every record
+    /// has been seen.
+    ///
+    /// Records are the elements named `record_tag` (at any depth), or the
+    /// children of the root element when no tag is given. Within a record:
+    /// - attributes become `@name` keys
+    /// - child elements become keys, and repeated children become arrays,
+    ///   so `-a` collapses them to `name[]` paths just like JSON arrays. An
+    ///   element that repeats in any record is an array in every record, even
+    ///   where it occurs once, so it is always reported under one path
+    /// - text of an element without attributes or children becomes its value,
+    ///   otherwise it is kept under a `#text` key
+    pub fn read_as_json_lines<R: BufRead>(
+        input: R,
+        record_tag: Option<&str>,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut reader = Reader::from_reader(input);
+        reader.trim_text(true);
+
+        let mut records = Vec::new();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut depth = 0usize;
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) => {
+                    if !stack.is_empty() || Self::is_record(&e, depth, record_tag) {
+                        stack.push(Self::open_frame(&e, &reader)?);
+                    }
+                    depth += 1;
+                }
+                Event::Empty(e) if !stack.is_empty() || Self::is_record(&e, depth, record_tag) => {
+                    let frame = Self::open_frame(&e, &reader)?;
+                    Self::close_frame(frame, &mut stack, &mut records)?;
+                }
+                Event::End(_) => {
+                    depth = depth.saturating_sub(1);
+                    if let Some(frame) = stack.pop() {
+                        Self::close_frame(frame, &mut stack, &mut records)?;
+                    }
+                }
+                Event::Text(e) => {
+                    if let Some(frame) = stack.last_mut() {
+                        frame.text.push_str(&e.unescape()?);
+                    }
+                }
+                Event::CData(e) => {
+                    if let Some(frame) = stack.last_mut() {
+                        frame.text.push_str(&String::from_utf8_lossy(&e));
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        // Elements seen repeating in any record are arrays in every record
+        let mut repeated = HashSet::new();
+        for record in &records {
+            Self::collect_repeated(record, &mut Vec::new(), &mut repeated);
+        }
+        records
+            .into_iter()
+            .map(|mut record| {
+                Self::wrap_repeated(&mut record, &mut Vec::new(), &repeated);
+                Ok(serde_json::to_string(&record)?)
+            })
+            .collect()
+    }
+
+    /// Paths, from the record element, of elements that occur more than once
+    /// under the same parent
+    fn collect_repeated(value: &Value, path: &mut Vec<String>, repeated: &mut HashSet<Vec<String>>) {
+        if let Value::Object(fields) = value {
+            for (key, child) in fields {
+                path.push(key.clone());
+                match child {
+                    Value::Array(items) => {
+                        repeated.insert(path.clone());
+                        for item in items {
+                            Self::collect_repeated(item, path, repeated);
+                        }
+                    }
+                    other => Self::collect_repeated(other, path, repeated),
+                }
+                path.pop();
+            }
+        }
+    }
+
+    /// Turn single occurrences of repeated elements into one-item arrays
+    fn wrap_repeated(value: &mut Value, path: &mut Vec<String>, repeated: &HashSet<Vec<String>>) {
+        if let Value::Object(fields) = value {
+            for (key, child) in fields.iter_mut() {
+                path.push(key.clone());
+                if repeated.contains(path) && !child.is_array() {
+                    *child = Value::Array(vec![child.take()]);
+                }
+                match child {
+                    Value::Array(items) => {
+                        for item in items {
+                            Self::wrap_repeated(item, path, repeated);
+                        }
+                    }
+                    other => Self::wrap_repeated(other, path, repeated),
+                }
+                path.pop();
+            }
+        }
+    }
+
+    fn is_record(element: &BytesStart, depth: usize, record_tag: Option<&str>) -> bool {
+        match record_tag {
+            Some(tag) => {
+                element.name().as_ref() == tag.as_bytes()
+                    || element.local_name().as_ref() == tag.as_bytes()
+            }
+            None => depth == 1,
+        }
+    }
+
+    fn open_frame<R>(element: &BytesStart, reader: &Reader<R>) -> Result<Frame, Box<dyn std::error::Error>> {
+        let mut fields = Map::new();
+        for attr in element.attributes() {
+            let attr = attr?;
+            let key = format!("@{}", String::from_utf8_lossy(attr.key.as_ref()));
+            fields.insert(key, Value::String(attr.decode_and_unescape_value(reader)?.into_owned()));
+        }
+        Ok(Frame {
+            name: String::from_utf8_lossy(element.name().as_ref()).into_owned(),
+            fields,
+            text: String::new(),
+            has_children: false,
+        })
+    }
+
+    /// Finish an element: attach it to its parent, or emit it as a record.
+    fn close_frame(
+        frame: Frame,
+        stack: &mut [Frame],
+        records: &mut Vec<Value>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let name = frame.name.clone();
+        let value = Self::frame_to_json(frame);
+
+        match stack.last_mut() {
+            Some(parent) => {
+                parent.has_children = true;
+                match parent.fields.get_mut(&name) {
+                    Some(Value::Array(items)) => items.push(value),
+                    Some(existing) => {
+                        let first = existing.take();
+                        *existing = Value::Array(vec![first, value]);
+                    }
+                    None => {
+                        parent.fields.insert(name, value);
+                    }
+                }
+            }
+            None => {
+                let record = match value {
+                    Value::Object(_) => value,
+                    // Leaf record elements are keyed by their own name
+                    other => {
+                        let mut map = Map::new();
+                        map.insert(name, other);
+                        Value::Object(map)
+                    }
+                };
+                records.push(record);
+            }
+        }
+        Ok(())
+    }
+
+    fn frame_to_json(frame: Frame) -> Value {
+        if frame.fields.is_empty() && !frame.has_children {
+            return Value::String(frame.text);
+        }
+        let mut fields = frame.fields;
+        if !frame.text.is_empty() {
+            fields.insert("#text".to_string(), Value::String(frame.text));
+        }
+        Value::Object(fields)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dummy implementation when xml feature is not enabled
+// ---------------------------------------------------------------------------
+
+#[cfg(not(feature = "xml"))]
+pub struct XmlReader;
+
+#[cfg(not(feature = "xml"))]
+impl XmlReader {
+    pub fn read_as_json_lines<R: BufRead>(
+        _input: R,
+        _record_tag: Option<&str>,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Err("XML support not enabled. Rebuild with --features xml".into())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+#[cfg(feature = "xml")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_from_root_children() {
+        let xml = r#"<?xml version="1.0"?>
+<payments>
+  <payment id="1" currency="GBP">
+    <amount>12.50</amount>
+    <payee><name>Acme &amp; Co</name></payee>
+    <line>a</line>
+    <line>b</line>
+  </payment>
+  <payment id="2"><amount>3</amount><note/></payment>
+</payments>"#;
+        let lines = XmlReader::read_as_json_lines(xml.as_bytes(), None).unwrap();
+        assert_eq!(lines.len(), 2);
+
+        let first: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["@id"], "1");
+        assert_eq!(first["amount"], "12.50");
+        assert_eq!(first["payee"]["name"], "Acme & Co");
+        assert_eq!(first["line"], serde_json::json!(["a", "b"]));
+
+        let second: Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(second["note"], "");
+    }
+
+    #[test]
+    fn test_single_occurrence_of_repeated_element_is_an_array() {
+        let xml = r#"<orders>
+  <order><item><sku>a</sku></item></order>
+  <order><item><sku>b</sku></item><item><sku>c</sku></item></order>
+  <order><total>1</total></order>
+</orders>"#;
+        let lines = XmlReader::read_as_json_lines(xml.as_bytes(), None).unwrap();
+        let records: Vec<Value> = lines.iter().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(records[0]["item"], serde_json::json!([{ "sku": "a" }]));
+        assert_eq!(records[1]["item"][1]["sku"], "c");
+        assert!(records[2].get("item").is_none());
+    }
+
+    #[test]
+    fn test_elements_that_never_repeat_stay_scalar() {
+        let xml = r#"<orders><order><id>1</id></order><order><id>2</id></order></orders>"#;
+        let lines = XmlReader::read_as_json_lines(xml.as_bytes(), None).unwrap();
+        let first: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["id"], "1");
+    }
+
+    #[test]
+    fn test_records_by_tag_with_text() {
+        let xml = r#"<feed><meta/><items><item code="x">Widget</item><item code="y">Gadget</item></items></feed>"#;
+        let lines = XmlReader::read_as_json_lines(xml.as_bytes(), Some("item")).unwrap();
+        assert_eq!(lines.len(), 2);
+        let first: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["@code"], "x");
+        assert_eq!(first["#text"], "Widget");
+    }
+}